@@ -0,0 +1,62 @@
+//! Element-wise arithmetic on the 100M element workload of `src/main.rs`
+//!
+//! Compares the fused kernels, sequential and multi-threaded, with the old
+//! implementation that zipped both inputs into a Vec of pairs and mapped it
+//! into a second Vec.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use numrs::{with_parallelism, ArrayND, Parallelism};
+
+const SIZE: usize = 100_000_000;
+
+/// The old two-pass `Add`: one Vec of pairs, then one Vec of sums
+fn add_two_pass(lhs: &[f64], rhs: &[f64]) -> Vec<f64> {
+    let pairs: Vec<(f64, f64)> = lhs.iter().copied().zip(rhs.iter().copied()).collect();
+    pairs.into_iter().map(|(x, y)| x + y).collect()
+}
+
+fn elementwise(c: &mut Criterion) {
+    let a: ArrayND = ArrayND::random(SIZE);
+    let b: ArrayND = ArrayND::random(SIZE);
+    let (a_vec, b_vec) = (a.to_vec(), b.to_vec());
+    let configs = [
+        ("seq", Parallelism::new().with_threshold(usize::MAX)),
+        ("par", Parallelism::new().with_threshold(0)),
+    ];
+
+    let mut group = c.benchmark_group("add_100m");
+    group.sample_size(10);
+    group.bench_function("two_pass", |bench| {
+        bench.iter(|| add_two_pass(&a_vec, &b_vec))
+    });
+    for (name, config) in &configs {
+        group.bench_function(format!("ref_ref_{}", name), |bench| {
+            bench.iter(|| with_parallelism(config, || &a + &b))
+        });
+        group.bench_function(format!("owned_ref_{}", name), |bench| {
+            bench.iter_batched(
+                || a.clone(),
+                |lhs| with_parallelism(config, || lhs + &b),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(format!("add_assign_{}", name), |bench| {
+            bench.iter_batched_ref(
+                || a.clone(),
+                |lhs| with_parallelism(config, || *lhs += &b),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(format!("mul_scalar_{}", name), |bench| {
+            bench.iter_batched(
+                || a.clone(),
+                |lhs| with_parallelism(config, || lhs * 2.),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, elementwise);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+//! NumPy-style broadcasting of shapes
+//!
+//! Shapes are aligned on their trailing axes, and an axis of length 1 is
+//! stretched to match the other operand. Missing leading axes count as 1.
+
+/// Shape resulting from broadcasting `lhs` against `rhs`, or `None` if they are incompatible
+pub(crate) fn broadcast_shapes(lhs: &[usize], rhs: &[usize]) -> Option<Vec<usize>> {
+    let ndim = lhs.len().max(rhs.len());
+    (0..ndim)
+        .map(
+            |axis| match (axis_len(lhs, ndim, axis), axis_len(rhs, ndim, axis)) {
+                (l, r) if l == r => Some(l),
+                (1, r) => Some(r),
+                (l, 1) => Some(l),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Strides that view an array of `shape` as if it had the larger shape `target`
+///
+/// Stretched and missing axes get a stride of 0 so the same elements are reused.
+pub(crate) fn broadcast_strides(
+    shape: &[usize],
+    strides: &[isize],
+    target: &[usize],
+) -> Vec<isize> {
+    let leading = target.len() - shape.len();
+    (0..target.len())
+        .map(|axis| {
+            if axis < leading || shape[axis - leading] != target[axis] {
+                0
+            } else {
+                strides[axis - leading]
+            }
+        })
+        .collect()
+}
+
+/// Length of `axis` once `shape` is left-padded with ones to `ndim` axes
+fn axis_len(shape: &[usize], ndim: usize, axis: usize) -> usize {
+    let leading = ndim - shape.len();
+    if axis < leading {
+        1
+    } else {
+        shape[axis - leading]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{broadcast_shapes, broadcast_strides};
+
+    #[test]
+    fn shapes() {
+        assert_eq!(broadcast_shapes(&[3, 1], &[1, 4]), Some(vec![3, 4]));
+        assert_eq!(broadcast_shapes(&[2, 3, 4], &[4]), Some(vec![2, 3, 4]));
+        assert_eq!(broadcast_shapes(&[5], &[2, 1]), Some(vec![2, 5]));
+        assert_eq!(broadcast_shapes(&[], &[2, 2]), Some(vec![2, 2]));
+        assert_eq!(broadcast_shapes(&[0], &[1]), Some(vec![0]));
+        assert_eq!(broadcast_shapes(&[3], &[4]), None);
+        assert_eq!(broadcast_shapes(&[2, 3], &[3, 3]), None);
+    }
+
+    #[test]
+    fn strides() {
+        assert_eq!(broadcast_strides(&[3, 1], &[1, 1], &[3, 4]), vec![1, 0]);
+        assert_eq!(broadcast_strides(&[4], &[1], &[2, 3, 4]), vec![0, 0, 1]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Runtime element types, type promotion and casting rules
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Element type of a `DynArray`, one variant per [`Element`](crate::Element) type
+///
+/// Exported to JavaScript, where it is used to pick the type of new Arrays.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DType {
+    Bool,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+}
+
+/// How strict `astype` is about casts that may lose information
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Casting {
+    /// Only casts that preserve every value, like `Int16` to `Float32`
+    Safe,
+    /// Safe casts, or casts within a kind or towards a higher kind, like
+    /// `Float64` to `Float32` or `UInt8` to `Int8`
+    SameKind,
+    /// Any cast, converting values like an `as` cast
+    Unsafe,
+}
+
+/// Kinds of element types, ordered so that every kind can represent the kinds before it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Bool,
+    UInt,
+    Int,
+    Float,
+}
+
+impl DType {
+    /// Whether the type is `Float32` or `Float64`
+    pub fn is_float(self) -> bool {
+        matches!(self, DType::Float32 | DType::Float64)
+    }
+
+    /// Whether the type is a signed or unsigned integer
+    pub fn is_integer(self) -> bool {
+        !self.is_float() && self != DType::Bool
+    }
+
+    /// Smallest type both `self` and `other` can be cast to safely
+    ///
+    /// This is the result type of a binary operation between Arrays of the two types:
+    ///
+    /// | operands                         | result                          |
+    /// |----------------------------------|---------------------------------|
+    /// | `Bool` and any `T`               | `T`                             |
+    /// | two types of the same kind       | the wider one                   |
+    /// | signed and unsigned integers     | a signed integer wide enough for both, `Float64` with `UInt64` |
+    /// | `Float32` and integers up to 16 bits | `Float32`                   |
+    /// | `Float32` and wider integers     | `Float64`                       |
+    /// | `Float64` and anything           | `Float64`                       |
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::DType;
+    /// assert_eq!(DType::Int32.promote(DType::Float32), DType::Float64);
+    /// assert_eq!(DType::UInt8.promote(DType::Int8), DType::Int16);
+    /// ```
+    pub fn promote(self, other: DType) -> DType {
+        let (lhs, rhs) = if self.kind() <= other.kind() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        match (lhs.kind(), rhs.kind()) {
+            (Kind::Bool, _) => rhs,
+            (lhs_kind, rhs_kind) if lhs_kind == rhs_kind => {
+                DType::from_kind(rhs_kind, lhs.bits().max(rhs.bits()))
+            }
+            (Kind::UInt, Kind::Int) if rhs.bits() > lhs.bits() => rhs,
+            (Kind::UInt, Kind::Int) if lhs.bits() < 64 => {
+                DType::from_kind(Kind::Int, lhs.bits() * 2)
+            }
+            (Kind::UInt, Kind::Int) => DType::Float64,
+            (_, Kind::Float) if lhs.bits() <= 16 => rhs,
+            _ => DType::Float64,
+        }
+    }
+
+    /// Smallest type that holds the scalar `value` exactly
+    ///
+    /// Whole numbers get the smallest unsigned type if they are non-negative
+    /// and the smallest signed type otherwise, like NumPy's `min_scalar_type`.
+    /// Anything else, or a whole number outside the 64 bit range, is `Float64`.
+    pub(crate) fn min_scalar_type(value: f64) -> DType {
+        let candidates = if value >= 0. {
+            [DType::UInt8, DType::UInt16, DType::UInt32, DType::UInt64]
+        } else {
+            [DType::Int8, DType::Int16, DType::Int32, DType::Int64]
+        };
+        candidates
+            .into_iter()
+            .find(|dtype| dtype.holds(value))
+            .unwrap_or(DType::Float64)
+    }
+
+    /// Whether `value` is a whole number within the range of the integer type,
+    /// always false for the other types
+    pub(crate) fn holds(self, value: f64) -> bool {
+        let (min, max) = match self {
+            DType::Int8 => (i8::MIN as i128, i8::MAX as i128),
+            DType::Int16 => (i16::MIN as i128, i16::MAX as i128),
+            DType::Int32 => (i32::MIN as i128, i32::MAX as i128),
+            DType::Int64 => (i64::MIN as i128, i64::MAX as i128),
+            DType::UInt8 => (0, u8::MAX as i128),
+            DType::UInt16 => (0, u16::MAX as i128),
+            DType::UInt32 => (0, u32::MAX as i128),
+            DType::UInt64 => (0, u64::MAX as i128),
+            DType::Bool | DType::Float32 | DType::Float64 => return false,
+        };
+        // `as` saturates, and any f64 beyond the i128 range is outside every integer range
+        value.fract() == 0. && (min..=max).contains(&(value as i128))
+    }
+
+    /// Whether Arrays of type `self` can be cast to `to` under the `casting` policy
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{Casting, DType};
+    /// assert!(DType::Int16.can_cast(DType::Float32, Casting::Safe));
+    /// assert!(!DType::Float64.can_cast(DType::Float32, Casting::Safe));
+    /// assert!(DType::Float64.can_cast(DType::Float32, Casting::SameKind));
+    /// assert!(!DType::Float64.can_cast(DType::Int64, Casting::SameKind));
+    /// ```
+    pub fn can_cast(self, to: DType, casting: Casting) -> bool {
+        let safe = self.promote(to) == to;
+        match casting {
+            Casting::Safe => safe,
+            Casting::SameKind => safe || self.kind() <= to.kind(),
+            Casting::Unsafe => true,
+        }
+    }
+
+    fn kind(self) -> Kind {
+        match self {
+            DType::Bool => Kind::Bool,
+            DType::UInt8 | DType::UInt16 | DType::UInt32 | DType::UInt64 => Kind::UInt,
+            DType::Int8 | DType::Int16 | DType::Int32 | DType::Int64 => Kind::Int,
+            DType::Float32 | DType::Float64 => Kind::Float,
+        }
+    }
+
+    /// Size of one element in bits
+    fn bits(self) -> u32 {
+        match self {
+            DType::Bool | DType::Int8 | DType::UInt8 => 8,
+            DType::Int16 | DType::UInt16 => 16,
+            DType::Int32 | DType::UInt32 | DType::Float32 => 32,
+            DType::Int64 | DType::UInt64 | DType::Float64 => 64,
+        }
+    }
+
+    fn from_kind(kind: Kind, bits: u32) -> DType {
+        match (kind, bits) {
+            (Kind::Bool, _) => DType::Bool,
+            (Kind::UInt, 8) => DType::UInt8,
+            (Kind::UInt, 16) => DType::UInt16,
+            (Kind::UInt, 32) => DType::UInt32,
+            (Kind::UInt, _) => DType::UInt64,
+            (Kind::Int, 8) => DType::Int8,
+            (Kind::Int, 16) => DType::Int16,
+            (Kind::Int, 32) => DType::Int32,
+            (Kind::Int, _) => DType::Int64,
+            (Kind::Float, 32) => DType::Float32,
+            (Kind::Float, _) => DType::Float64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Casting, DType};
+
+    const ALL: [DType; 11] = [
+        DType::Bool,
+        DType::Int8,
+        DType::Int16,
+        DType::Int32,
+        DType::Int64,
+        DType::UInt8,
+        DType::UInt16,
+        DType::UInt32,
+        DType::UInt64,
+        DType::Float32,
+        DType::Float64,
+    ];
+
+    #[test]
+    fn promote() {
+        assert_eq!(DType::Int32.promote(DType::Float32), DType::Float64);
+        assert_eq!(DType::Int16.promote(DType::Float32), DType::Float32);
+        assert_eq!(DType::UInt8.promote(DType::Int8), DType::Int16);
+        assert_eq!(DType::UInt8.promote(DType::Int32), DType::Int32);
+        assert_eq!(DType::UInt32.promote(DType::Int32), DType::Int64);
+        assert_eq!(DType::UInt64.promote(DType::Int8), DType::Float64);
+        assert_eq!(DType::UInt16.promote(DType::UInt64), DType::UInt64);
+        assert_eq!(DType::Bool.promote(DType::Int8), DType::Int8);
+        assert_eq!(DType::Float32.promote(DType::Float64), DType::Float64);
+    }
+
+    #[test]
+    fn promote_is_symmetric_and_safe() {
+        for lhs in ALL {
+            assert_eq!(lhs.promote(lhs), lhs);
+            for rhs in ALL {
+                let result = lhs.promote(rhs);
+                assert_eq!(result, rhs.promote(lhs));
+                assert!(lhs.can_cast(result, Casting::Safe), "{:?} {:?}", lhs, rhs);
+                assert!(rhs.can_cast(result, Casting::Safe), "{:?} {:?}", lhs, rhs);
+            }
+        }
+    }
+
+    #[test]
+    fn min_scalar_type() {
+        assert_eq!(DType::min_scalar_type(0.), DType::UInt8);
+        assert_eq!(DType::min_scalar_type(256.), DType::UInt16);
+        assert_eq!(DType::min_scalar_type(-1.), DType::Int8);
+        assert_eq!(DType::min_scalar_type(-129.), DType::Int16);
+        assert_eq!(DType::min_scalar_type(u64::MAX as f64), DType::Float64);
+        assert_eq!(DType::min_scalar_type(i64::MIN as f64), DType::Int64);
+        assert_eq!(DType::min_scalar_type(0.5), DType::Float64);
+        assert_eq!(DType::min_scalar_type(f64::NAN), DType::Float64);
+        assert_eq!(DType::min_scalar_type(f64::NEG_INFINITY), DType::Float64);
+        assert!(!DType::Int64.holds(2f64.powi(63)));
+        assert!(!DType::Float64.holds(1.));
+    }
+
+    #[test]
+    fn can_cast() {
+        assert!(DType::Bool.can_cast(DType::UInt8, Casting::Safe));
+        assert!(DType::UInt32.can_cast(DType::Float64, Casting::Safe));
+        assert!(!DType::Int64.can_cast(DType::Int32, Casting::Safe));
+        assert!(DType::Int64.can_cast(DType::Int32, Casting::SameKind));
+        assert!(DType::UInt8.can_cast(DType::Int8, Casting::SameKind));
+        assert!(!DType::Int8.can_cast(DType::UInt8, Casting::SameKind));
+        assert!(!DType::Float32.can_cast(DType::Bool, Casting::SameKind));
+        assert!(DType::Float32.can_cast(DType::Bool, Casting::Unsafe));
+    }
+}
@@ -0,0 +1,1061 @@
+//! Arrays whose element type is only known at runtime
+//!
+//! `DynArray` holds an `ArrayND` of any [`Element`] type and forwards every
+//! method to the typed implementation, which is what the wasm bindings build on.
+
+use std::{
+    fmt::Display,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::{
+    error, ops, ArrayND, BinaryUfunc, Casting, DType, Element, NumericBinaryOp, NumericUnaryOp,
+    NumrsError, Result, UnaryUfunc,
+};
+
+/// Array with a runtime element type
+///
+/// Binary operators between Arrays of different types first convert both to
+/// the type given by [`DType::promote`]. Integer results wrap around on
+/// overflow like those of the typed Arrays.
+///
+/// # Example
+/// ```
+/// use numrs::{DType, DynArray};
+/// let array = DynArray::from_f64_vec(vec![1.5, 2.5, 3.5], vec![3], DType::Int32);
+/// assert_eq!(array.dtype(), DType::Int32);
+/// assert_eq!(array.to_f64_vec(), vec![1., 2., 3.]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynArray {
+    Bool(ArrayND<bool>),
+    Int8(ArrayND<i8>),
+    Int16(ArrayND<i16>),
+    Int32(ArrayND<i32>),
+    Int64(ArrayND<i64>),
+    UInt8(ArrayND<u8>),
+    UInt16(ArrayND<u16>),
+    UInt32(ArrayND<u32>),
+    UInt64(ArrayND<u64>),
+    Float32(ArrayND<f32>),
+    Float64(ArrayND<f64>),
+}
+
+/// Evaluates `$body` with `$array` bound to the typed Array inside a `DynArray`
+macro_rules! dispatch {
+    ($dyn:expr, $array:ident => $body:expr) => {
+        match $dyn {
+            DynArray::Bool($array) => $body,
+            DynArray::Int8($array) => $body,
+            DynArray::Int16($array) => $body,
+            DynArray::Int32($array) => $body,
+            DynArray::Int64($array) => $body,
+            DynArray::UInt8($array) => $body,
+            DynArray::UInt16($array) => $body,
+            DynArray::UInt32($array) => $body,
+            DynArray::UInt64($array) => $body,
+            DynArray::Float32($array) => $body,
+            DynArray::Float64($array) => $body,
+        }
+    };
+}
+
+/// Like `dispatch!`, but wraps the resulting Array back into the same variant
+macro_rules! map_dispatch {
+    ($dyn:expr, $array:ident => $body:expr) => {
+        match $dyn {
+            DynArray::Bool($array) => DynArray::Bool($body),
+            DynArray::Int8($array) => DynArray::Int8($body),
+            DynArray::Int16($array) => DynArray::Int16($body),
+            DynArray::Int32($array) => DynArray::Int32($body),
+            DynArray::Int64($array) => DynArray::Int64($body),
+            DynArray::UInt8($array) => DynArray::UInt8($body),
+            DynArray::UInt16($array) => DynArray::UInt16($body),
+            DynArray::UInt32($array) => DynArray::UInt32($body),
+            DynArray::UInt64($array) => DynArray::UInt64($body),
+            DynArray::Float32($array) => DynArray::Float32($body),
+            DynArray::Float64($array) => DynArray::Float64($body),
+        }
+    };
+}
+
+/// Like `dispatch!` for operations that need arithmetic, with a separate arm for bool Arrays
+macro_rules! dispatch_numeric {
+    ($dyn:expr, $array:ident => $body:expr, Bool($bool:pat) => $bool_body:expr) => {
+        match $dyn {
+            DynArray::Bool($bool) => $bool_body,
+            DynArray::Int8($array) => $body,
+            DynArray::Int16($array) => $body,
+            DynArray::Int32($array) => $body,
+            DynArray::Int64($array) => $body,
+            DynArray::UInt8($array) => $body,
+            DynArray::UInt16($array) => $body,
+            DynArray::UInt32($array) => $body,
+            DynArray::UInt64($array) => $body,
+            DynArray::Float32($array) => $body,
+            DynArray::Float64($array) => $body,
+        }
+    };
+}
+
+/// Builds a `DynArray` of the given `DType`, evaluating `$body` with `$t` as the element type
+macro_rules! with_dtype {
+    ($dtype:expr, $t:ident => $body:expr) => {
+        match $dtype {
+            DType::Bool => {
+                type $t = bool;
+                DynArray::Bool($body)
+            }
+            DType::Int8 => {
+                type $t = i8;
+                DynArray::Int8($body)
+            }
+            DType::Int16 => {
+                type $t = i16;
+                DynArray::Int16($body)
+            }
+            DType::Int32 => {
+                type $t = i32;
+                DynArray::Int32($body)
+            }
+            DType::Int64 => {
+                type $t = i64;
+                DynArray::Int64($body)
+            }
+            DType::UInt8 => {
+                type $t = u8;
+                DynArray::UInt8($body)
+            }
+            DType::UInt16 => {
+                type $t = u16;
+                DynArray::UInt16($body)
+            }
+            DType::UInt32 => {
+                type $t = u32;
+                DynArray::UInt32($body)
+            }
+            DType::UInt64 => {
+                type $t = u64;
+                DynArray::UInt64($body)
+            }
+            DType::Float32 => {
+                type $t = f32;
+                DynArray::Float32($body)
+            }
+            DType::Float64 => {
+                type $t = f64;
+                DynArray::Float64($body)
+            }
+        }
+    };
+}
+
+macro_rules! impl_from_array {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl From<ArrayND<$t>> for DynArray {
+                fn from(array: ArrayND<$t>) -> DynArray {
+                    DynArray::$variant(array)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_array!(
+    bool => Bool,
+    i8 => Int8,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    u8 => UInt8,
+    u16 => UInt16,
+    u32 => UInt32,
+    u64 => UInt64,
+    f32 => Float32,
+    f64 => Float64
+);
+
+/// Converts a Vec of `f64` to any element type, like an `as` cast
+fn cast_vec<T: Element>(data: Vec<f64>) -> Vec<T> {
+    data.into_iter().map(T::from_f64).collect()
+}
+
+impl DynArray {
+    /// Creates an Array of type `dtype` from `f64` values, converting them like an `as` cast
+    ///
+    /// Panics if the number of elements does not match the shape.
+    pub fn from_f64_vec(data: Vec<f64>, shape: Vec<usize>, dtype: DType) -> DynArray {
+        error::or_panic(DynArray::try_from_f64_vec(data, shape, dtype))
+    }
+
+    /// Creates an Array of type `dtype` from `f64` values, failing if the
+    /// number of elements does not match the shape
+    pub fn try_from_f64_vec(data: Vec<f64>, shape: Vec<usize>, dtype: DType) -> Result<DynArray> {
+        Ok(with_dtype!(dtype, T => ArrayND::try_from_shape_vec(cast_vec::<T>(data), shape)?))
+    }
+
+    /// Generates a random 1D Array of type `dtype`
+    ///
+    /// Floats are drawn from `[0, 1)` and integers and bools from their whole range.
+    pub fn random(size: usize, dtype: DType) -> DynArray {
+        with_dtype!(dtype, T => ArrayND::<T>::random(size))
+    }
+
+    /// Evenly spaced values in `[start, stop)`, converted to `dtype`
+    ///
+    /// The values are computed in `f64`, so integer Arrays get truncated values.
+    pub fn arange(start: f64, stop: f64, step: f64, dtype: DType) -> DynArray {
+        let data = ArrayND::arange(start, stop, step).to_vec();
+        let shape = vec![data.len()];
+        DynArray::from_f64_vec(data, shape, dtype)
+    }
+
+    /// Copies the Array into one of type `dtype`
+    ///
+    /// Fails if `casting` does not allow converting the current type to `dtype`.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{Casting, DType, DynArray};
+    /// let array = DynArray::arange(0., 3., 1., DType::Int64);
+    /// assert!(array.astype(DType::Int8, Casting::Safe).is_err());
+    /// let array = array.astype(DType::Int8, Casting::SameKind).unwrap();
+    /// assert_eq!(array.dtype(), DType::Int8);
+    /// ```
+    pub fn astype(&self, dtype: DType, casting: Casting) -> Result<DynArray> {
+        let from = self.dtype();
+        if !from.can_cast(dtype, casting) {
+            return Err(NumrsError::InvalidCast {
+                from,
+                to: dtype,
+                casting,
+            });
+        }
+        Ok(self.cast(dtype))
+    }
+
+    /// Copies the Array into one of type `dtype`, converting like an `as` cast
+    fn cast(&self, dtype: DType) -> DynArray {
+        dispatch!(self, array => with_dtype!(dtype, T => array.cast::<T>()))
+    }
+
+    /// Runtime element type of the Array
+    pub fn dtype(&self) -> DType {
+        dispatch!(self, array => element_dtype(array))
+    }
+
+    /// Length of each axis of the Array
+    pub fn shape(&self) -> &[usize] {
+        dispatch!(self, array => array.shape())
+    }
+
+    /// Number of axes of the Array
+    pub fn ndim(&self) -> usize {
+        dispatch!(self, array => array.ndim())
+    }
+
+    /// Copies the elements into a Vec of `f64` in row-major order
+    pub fn to_f64_vec(&self) -> Vec<f64> {
+        dispatch!(self, array => array.iter().map(|x| x.to_f64()).collect())
+    }
+
+    /// Element at position `index` in row-major order, converted to `f64`
+    ///
+    /// Negative indices count from the end like in Python, `None` if the index
+    /// is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{DType, DynArray};
+    /// let array = DynArray::from_f64_vec(vec![1., 2., 3.], vec![3], DType::Int32);
+    /// assert_eq!(array.get(0), Some(1.));
+    /// assert_eq!(array.get(-1), Some(3.));
+    /// assert_eq!(array.get(3), None);
+    /// ```
+    pub fn get(&self, index: isize) -> Option<f64> {
+        let size = self.shape().iter().product::<usize>() as isize;
+        let index = if index < 0 { index + size } else { index };
+        if index < 0 {
+            return None;
+        }
+        dispatch!(self, array => array.get(index as usize).map(|x| x.to_f64()))
+    }
+
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        dispatch!(self, array => array.to_string())
+    }
+
+    /// Smallest element, converted to `f64`
+    ///
+    /// Panics if a non-float Array is empty.
+    pub fn min(&self) -> f64 {
+        error::or_panic(self.try_min())
+    }
+
+    /// Smallest element, failing if a non-float Array is empty
+    pub fn try_min(&self) -> Result<f64> {
+        dispatch!(self, array => array.try_min().map(Element::to_f64))
+    }
+
+    /// Largest element, converted to `f64`
+    ///
+    /// Panics if a non-float Array is empty.
+    pub fn max(&self) -> f64 {
+        error::or_panic(self.try_max())
+    }
+
+    /// Largest element, failing if a non-float Array is empty
+    pub fn try_max(&self) -> Result<f64> {
+        dispatch!(self, array => array.try_max().map(Element::to_f64))
+    }
+
+    /// Smallest element ignoring NaN, converted to `f64`
+    ///
+    /// Panics if a non-float Array is empty.
+    pub fn nanmin(&self) -> f64 {
+        error::or_panic(self.try_nanmin())
+    }
+
+    /// Smallest element ignoring NaN, failing if a non-float Array is empty
+    pub fn try_nanmin(&self) -> Result<f64> {
+        dispatch!(self, array => array.try_nanmin().map(Element::to_f64))
+    }
+
+    /// Largest element ignoring NaN, converted to `f64`
+    ///
+    /// Panics if a non-float Array is empty.
+    pub fn nanmax(&self) -> f64 {
+        error::or_panic(self.try_nanmax())
+    }
+
+    /// Largest element ignoring NaN, failing if a non-float Array is empty
+    pub fn try_nanmax(&self) -> Result<f64> {
+        dispatch!(self, array => array.try_nanmax().map(Element::to_f64))
+    }
+
+    /// Sum of the elements, converted to `f64`
+    ///
+    /// Integers are summed in a wider type, so the sum cannot overflow. Bool
+    /// Arrays count their `true` elements.
+    pub fn sum(&self) -> f64 {
+        dispatch_numeric!(
+            self,
+            array => array.sum_f64(),
+            Bool(array) => array.iter().filter(|&&x| x).count() as f64
+        )
+    }
+
+    /// Sum of the elements treating NaN as zero, converted to `f64`
+    pub fn nansum(&self) -> f64 {
+        dispatch_numeric!(self, array => array.nansum_f64(), Bool(_) => self.sum())
+    }
+
+    /// Arithmetic mean of the elements
+    pub fn mean(&self) -> f64 {
+        self.sum() / self.shape().iter().product::<usize>() as f64
+    }
+
+    /// Arithmetic mean of the elements that are not NaN
+    pub fn nanmean(&self) -> f64 {
+        dispatch_numeric!(self, array => array.nanmean(), Bool(_) => self.mean())
+    }
+
+    /// Returns a 1D copy of the Array in row-major order
+    pub fn flatten(&self) -> DynArray {
+        map_dispatch!(self, array => array.flatten())
+    }
+
+    /// Converts the Array into 1D, only copying the data if it is not contiguous
+    pub fn ravel(self) -> DynArray {
+        map_dispatch!(self, array => array.ravel())
+    }
+
+    /// Reverses the order of the axes
+    pub fn transpose(self) -> DynArray {
+        map_dispatch!(self, array => array.transpose())
+    }
+
+    /// Gives the Array a new shape with the same number of elements
+    pub fn reshape(self, shape: &[isize]) -> DynArray {
+        error::or_panic(self.try_reshape(shape))
+    }
+
+    /// Gives the Array a new shape, failing if it does not hold the same number of elements
+    pub fn try_reshape(self, shape: &[isize]) -> Result<DynArray> {
+        Ok(map_dispatch!(self, array => array.try_reshape(shape)?))
+    }
+
+    /// Applies `op` between every element and `rhs` converted to the element type
+    ///
+    /// Integer Arrays are first promoted to a type that also holds the scalar
+    /// if it does not fit their own, like NumPy 1 does: to a wider or signed
+    /// integer type for whole numbers and to `Float64` for anything else. Fails
+    /// for bool Arrays, which have no arithmetic.
+    fn scalar_op(self, rhs: f64, op: Op) -> Result<DynArray> {
+        if self.dtype().is_integer() && !self.dtype().holds(rhs) {
+            let dtype = self.dtype().promote(DType::min_scalar_type(rhs));
+            return self.cast(dtype).scalar_op(rhs, op);
+        }
+        dispatch_numeric!(
+            self,
+            array => Ok(op.scalar(array, rhs)?.into()),
+            Bool(_) => Err(bool_arithmetic())
+        )
+    }
+
+    /// Applies `op` element-wise, promoting both Arrays to a common type first
+    ///
+    /// Fails if the shapes cannot be broadcast together or both Arrays are bool.
+    fn array_op(self, rhs: &DynArray, op: Op) -> Result<DynArray> {
+        ops::result_shape(self.shape(), rhs.shape())?;
+        let dtype = self.dtype().promote(rhs.dtype());
+        if self.dtype() != dtype {
+            return self.cast(dtype).array_op(rhs, op);
+        }
+        if rhs.dtype() != dtype {
+            return self.array_op(&rhs.cast(dtype), op);
+        }
+        Ok(match (self, rhs) {
+            (DynArray::Int8(a), DynArray::Int8(b)) => DynArray::Int8(op.array(a, b)?),
+            (DynArray::Int16(a), DynArray::Int16(b)) => DynArray::Int16(op.array(a, b)?),
+            (DynArray::Int32(a), DynArray::Int32(b)) => DynArray::Int32(op.array(a, b)?),
+            (DynArray::Int64(a), DynArray::Int64(b)) => DynArray::Int64(op.array(a, b)?),
+            (DynArray::UInt8(a), DynArray::UInt8(b)) => DynArray::UInt8(op.array(a, b)?),
+            (DynArray::UInt16(a), DynArray::UInt16(b)) => DynArray::UInt16(op.array(a, b)?),
+            (DynArray::UInt32(a), DynArray::UInt32(b)) => DynArray::UInt32(op.array(a, b)?),
+            (DynArray::UInt64(a), DynArray::UInt64(b)) => DynArray::UInt64(op.array(a, b)?),
+            (DynArray::Float32(a), DynArray::Float32(b)) => DynArray::Float32(op.array(a, b)?),
+            (DynArray::Float64(a), DynArray::Float64(b)) => DynArray::Float64(op.array(a, b)?),
+            (DynArray::Bool(_), DynArray::Bool(_)) => return Err(bool_arithmetic()),
+            _ => unreachable!("operands were promoted to the same dtype"),
+        })
+    }
+}
+
+impl<F: NumericUnaryOp> UnaryUfunc<F> {
+    /// Applies the function to every element of an Array of any numeric type
+    ///
+    /// Panics for bool Arrays.
+    pub fn call_dyn(&self, array: &DynArray) -> DynArray {
+        error::or_panic(self.try_call_dyn(array))
+    }
+
+    /// Applies the function to every element of an Array of any numeric
+    /// type, failing for bool Arrays
+    pub fn try_call_dyn(&self, array: &DynArray) -> Result<DynArray> {
+        dispatch_numeric!(
+            array,
+            array => Ok(self.call(array).into()),
+            Bool(_) => Err(bool_arithmetic())
+        )
+    }
+}
+
+impl<F: NumericBinaryOp> BinaryUfunc<F> {
+    /// Applies the function between two Arrays of any numeric type, promoting
+    /// both to a common type first
+    ///
+    /// Panics if the shapes cannot be broadcast together or both Arrays are bool.
+    pub fn call_dyn(&self, lhs: &DynArray, rhs: &DynArray) -> DynArray {
+        error::or_panic(self.try_call_dyn(lhs, rhs))
+    }
+
+    /// Applies the function between two Arrays of any numeric type, promoting
+    /// both to a common type first
+    ///
+    /// Fails if the shapes cannot be broadcast together or both Arrays are bool.
+    pub fn try_call_dyn(&self, lhs: &DynArray, rhs: &DynArray) -> Result<DynArray> {
+        ops::result_shape(lhs.shape(), rhs.shape())?;
+        let dtype = lhs.dtype().promote(rhs.dtype());
+        if lhs.dtype() != dtype {
+            return self.try_call_dyn(&lhs.cast(dtype), rhs);
+        }
+        if rhs.dtype() != dtype {
+            return self.try_call_dyn(lhs, &rhs.cast(dtype));
+        }
+        Ok(match (lhs, rhs) {
+            (DynArray::Int8(a), DynArray::Int8(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Int16(a), DynArray::Int16(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Int32(a), DynArray::Int32(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Int64(a), DynArray::Int64(b)) => self.try_call(a, b)?.into(),
+            (DynArray::UInt8(a), DynArray::UInt8(b)) => self.try_call(a, b)?.into(),
+            (DynArray::UInt16(a), DynArray::UInt16(b)) => self.try_call(a, b)?.into(),
+            (DynArray::UInt32(a), DynArray::UInt32(b)) => self.try_call(a, b)?.into(),
+            (DynArray::UInt64(a), DynArray::UInt64(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Float32(a), DynArray::Float32(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Float64(a), DynArray::Float64(b)) => self.try_call(a, b)?.into(),
+            (DynArray::Bool(_), DynArray::Bool(_)) => return Err(bool_arithmetic()),
+            _ => unreachable!("operands were promoted to the same dtype"),
+        })
+    }
+}
+
+fn bool_arithmetic() -> NumrsError {
+    NumrsError::UnsupportedDType("arithmetic is not supported for bool arrays".to_string())
+}
+
+fn element_dtype<T: Element>(_: &ArrayND<T>) -> DType {
+    T::DTYPE
+}
+
+/// Arithmetic operator applied by `DynArray`
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    /// Applies the operator between every element and `rhs` converted to the
+    /// element type, failing on integer division by zero or overflow
+    fn scalar<T>(self, lhs: ArrayND<T>, rhs: f64) -> Result<ArrayND<T>>
+    where
+        T: Element + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+    {
+        let rhs = T::from_f64(rhs);
+        Ok(match self {
+            Op::Add => lhs + rhs,
+            Op::Sub => lhs - rhs,
+            Op::Mul => lhs * rhs,
+            Op::Div if T::DTYPE.is_float() => lhs / rhs,
+            Op::Div => ops::div_into(lhs, &ArrayND::new(vec![rhs]).operand())?,
+        })
+    }
+
+    /// Applies the operator element-wise with broadcasting, failing on integer
+    /// division by zero or overflow
+    fn array<T>(self, lhs: ArrayND<T>, rhs: &ArrayND<T>) -> Result<ArrayND<T>>
+    where
+        T: Element + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+    {
+        Ok(match self {
+            Op::Add => lhs + rhs,
+            Op::Sub => lhs - rhs,
+            Op::Mul => lhs * rhs,
+            Op::Div => ops::div_into(lhs, &rhs.operand())?,
+        })
+    }
+}
+
+macro_rules! impl_dyn_op {
+    ($trait:ident, $method:ident, $op:ident, $try_method:ident, $try_scalar_method:ident) => {
+        impl DynArray {
+            /// Element-wise operation with promotion and broadcasting, failing
+            /// if the shapes are incompatible, both Arrays are bool or an
+            /// integer division has a zero divisor
+            pub fn $try_method(self, rhs: &DynArray) -> Result<DynArray> {
+                self.array_op(rhs, Op::$op)
+            }
+
+            /// Operation with a scalar converted to the element type, failing for
+            /// bool Arrays or integer division by zero
+            ///
+            /// Integer Arrays are promoted if the scalar is outside their range or
+            /// not a whole number.
+            pub fn $try_scalar_method(self, rhs: f64) -> Result<DynArray> {
+                self.scalar_op(rhs, Op::$op)
+            }
+        }
+
+        impl $trait<DynArray> for DynArray {
+            type Output = DynArray;
+
+            fn $method(self, rhs: DynArray) -> DynArray {
+                error::or_panic(self.array_op(&rhs, Op::$op))
+            }
+        }
+
+        impl $trait<&DynArray> for DynArray {
+            type Output = DynArray;
+
+            fn $method(self, rhs: &DynArray) -> DynArray {
+                error::or_panic(self.array_op(rhs, Op::$op))
+            }
+        }
+
+        impl $trait<&DynArray> for &DynArray {
+            type Output = DynArray;
+
+            fn $method(self, rhs: &DynArray) -> DynArray {
+                error::or_panic(self.clone().array_op(rhs, Op::$op))
+            }
+        }
+
+        impl $trait<f64> for DynArray {
+            type Output = DynArray;
+
+            fn $method(self, rhs: f64) -> DynArray {
+                error::or_panic(self.scalar_op(rhs, Op::$op))
+            }
+        }
+    };
+}
+
+impl_dyn_op!(Add, add, Add, try_add, try_add_scalar);
+impl_dyn_op!(Sub, sub, Sub, try_sub, try_sub_scalar);
+impl_dyn_op!(Mul, mul, Mul, try_mul, try_mul_scalar);
+impl_dyn_op!(Div, div, Div, try_div, try_div_scalar);
+
+impl DynArray {
+    /// Converts integer and bool Arrays to `Float64` in place, for the math functions
+    fn make_float(&mut self) {
+        if !self.dtype().is_float() {
+            *self = self.cast(DType::Float64);
+        }
+    }
+
+    /// Converts bool Arrays to `Float64` in place, for the math functions that keep integer types
+    fn make_float_if_bool(&mut self) {
+        if self.dtype() == DType::Bool {
+            *self = self.cast(DType::Float64);
+        }
+    }
+}
+
+/// Float type the math functions compute in for operands promoting to `dtype`
+fn float_dtype(dtype: DType) -> DType {
+    if dtype.is_float() {
+        dtype
+    } else {
+        DType::Float64
+    }
+}
+
+macro_rules! impl_dyn_unary {
+    ($($method:ident, $method_mut:ident;)*) => {
+        impl DynArray {
+            $(
+                #[doc = concat!("[`ArrayND::", stringify!($method), "`], converting integer and bool Arrays to `Float64` first")]
+                pub fn $method(mut self) -> DynArray {
+                    self.$method_mut();
+                    self
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($method_mut), "`], converting integer and bool Arrays to `Float64` first")]
+                pub fn $method_mut(&mut self) {
+                    self.make_float();
+                    match self {
+                        DynArray::Float32(array) => array.$method_mut(),
+                        DynArray::Float64(array) => array.$method_mut(),
+                        _ => unreachable!("the Array was converted to a float type"),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! impl_dyn_integer_unary {
+    ($($method:ident, $method_mut:ident, |$array:ident| $integer:expr;)*) => {
+        impl DynArray {
+            $(
+                #[doc = concat!("[`ArrayND::", stringify!($method), "`], keeping the type of integer Arrays")]
+                ///
+                /// Bool Arrays are converted to `Float64` first.
+                pub fn $method(mut self) -> DynArray {
+                    self.$method_mut();
+                    self
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($method_mut), "`], keeping the type of integer Arrays")]
+                ///
+                /// Bool Arrays are converted to `Float64` first.
+                pub fn $method_mut(&mut self) {
+                    self.make_float_if_bool();
+                    match self {
+                        DynArray::Float32(array) => array.$method_mut(),
+                        DynArray::Float64(array) => array.$method_mut(),
+                        DynArray::Int8($array) => $integer,
+                        DynArray::Int16($array) => $integer,
+                        DynArray::Int32($array) => $integer,
+                        DynArray::Int64($array) => $integer,
+                        DynArray::UInt8($array) => $integer,
+                        DynArray::UInt16($array) => $integer,
+                        DynArray::UInt32($array) => $integer,
+                        DynArray::UInt64($array) => $integer,
+                        DynArray::Bool(_) => unreachable!("bool Arrays were converted to Float64"),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+/// Absolute value of an integer, wrapping for the smallest signed value like `wrapping_abs`
+fn integer_abs<T: Element>(x: T) -> T {
+    T::from_i128(x.to_i128().abs())
+}
+
+/// -1, 0 or 1 depending on the sign of an integer
+fn integer_sign<T: Element>(x: T) -> T {
+    T::from_i128(x.to_i128().signum())
+}
+
+macro_rules! impl_dyn_binary {
+    ($(
+        $method:ident, $try_method:ident, $method_mut:ident, $try_method_mut:ident,
+        $scalar_method:ident, $scalar_method_mut:ident;
+    )*) => {
+        impl DynArray {
+            $(
+                #[doc = concat!("[`ArrayND::", stringify!($method), "`] in the float type both Arrays promote to")]
+                ///
+                /// Panics if the shapes cannot be broadcast together.
+                pub fn $method(self, rhs: &DynArray) -> DynArray {
+                    error::or_panic(self.$try_method(rhs))
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($method), "`] in the float type both Arrays promote to,")]
+                /// failing if the shapes are incompatible
+                pub fn $try_method(self, rhs: &DynArray) -> Result<DynArray> {
+                    let dtype = float_dtype(self.dtype().promote(rhs.dtype()));
+                    if self.dtype() != dtype {
+                        return self.cast(dtype).$try_method(rhs);
+                    }
+                    if rhs.dtype() != dtype {
+                        return self.$try_method(&rhs.cast(dtype));
+                    }
+                    Ok(match (self, rhs) {
+                        (DynArray::Float32(a), DynArray::Float32(b)) => DynArray::Float32(a.$try_method(b)?),
+                        (DynArray::Float64(a), DynArray::Float64(b)) => DynArray::Float64(a.$try_method(b)?),
+                        _ => unreachable!("operands were promoted to the same float dtype"),
+                    })
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($method_mut), "`] in the float type both Arrays promote to")]
+                ///
+                /// Panics if `rhs` cannot be broadcast to the shape of the Array.
+                pub fn $method_mut(&mut self, rhs: &DynArray) {
+                    error::or_panic(self.$try_method_mut(rhs))
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($method_mut), "`] in the float type both Arrays promote to,")]
+                /// failing without modifying the Array if `rhs` cannot be broadcast to its shape
+                pub fn $try_method_mut(&mut self, rhs: &DynArray) -> Result<()> {
+                    let dtype = float_dtype(self.dtype().promote(rhs.dtype()));
+                    if self.dtype() != dtype {
+                        let mut converted = self.cast(dtype);
+                        converted.$try_method_mut(rhs)?;
+                        *self = converted;
+                        return Ok(());
+                    }
+                    if rhs.dtype() != dtype {
+                        return self.$try_method_mut(&rhs.cast(dtype));
+                    }
+                    match (self, rhs) {
+                        (DynArray::Float32(a), DynArray::Float32(b)) => a.$try_method_mut(b),
+                        (DynArray::Float64(a), DynArray::Float64(b)) => a.$try_method_mut(b),
+                        _ => unreachable!("operands were promoted to the same float dtype"),
+                    }
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($scalar_method), "`], converting integer and bool Arrays to `Float64` first")]
+                pub fn $scalar_method(mut self, rhs: f64) -> DynArray {
+                    self.$scalar_method_mut(rhs);
+                    self
+                }
+
+                #[doc = concat!("[`ArrayND::", stringify!($scalar_method_mut), "`], converting integer and bool Arrays to `Float64` first")]
+                pub fn $scalar_method_mut(&mut self, rhs: f64) {
+                    self.make_float();
+                    match self {
+                        DynArray::Float32(array) => array.$scalar_method_mut(rhs as f32),
+                        DynArray::Float64(array) => array.$scalar_method_mut(rhs),
+                        _ => unreachable!("the Array was converted to a float type"),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl_dyn_unary!(
+    exp, exp_mut;
+    exp2, exp2_mut;
+    ln, ln_mut;
+    log2, log2_mut;
+    log10, log10_mut;
+    log1p, log1p_mut;
+    expm1, expm1_mut;
+    sqrt, sqrt_mut;
+    cbrt, cbrt_mut;
+    sin, sin_mut;
+    cos, cos_mut;
+    tan, tan_mut;
+    asin, asin_mut;
+    acos, acos_mut;
+    atan, atan_mut;
+    sinh, sinh_mut;
+    cosh, cosh_mut;
+    tanh, tanh_mut;
+    asinh, asinh_mut;
+    acosh, acosh_mut;
+    atanh, atanh_mut;
+);
+
+impl_dyn_integer_unary!(
+    abs, abs_mut, |array| array.map_assign(None, integer_abs);
+    sign, sign_mut, |array| array.map_assign(None, integer_sign);
+    floor, floor_mut, |_array| {};
+    ceil, ceil_mut, |_array| {};
+    round, round_mut, |_array| {};
+    trunc, trunc_mut, |_array| {};
+);
+
+impl_dyn_binary!(
+    pow, try_pow, pow_mut, try_pow_mut, pow_scalar, pow_scalar_mut;
+    hypot, try_hypot, hypot_mut, try_hypot_mut, hypot_scalar, hypot_scalar_mut;
+    atan2, try_atan2, atan2_mut, try_atan2_mut, atan2_scalar, atan2_scalar_mut;
+    maximum, try_maximum, maximum_mut, try_maximum_mut, maximum_scalar, maximum_scalar_mut;
+    minimum, try_minimum, minimum_mut, try_minimum_mut, minimum_scalar, minimum_scalar_mut;
+);
+
+impl Display for DynArray {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        dispatch!(self, array => write!(f, "{} dtype: {:?}", array, self.dtype()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayND, Casting, DType, DynArray, NumrsError};
+
+    #[test]
+    fn dtype() {
+        let array = DynArray::from_f64_vec(vec![1., 0., 2.], vec![3], DType::Bool);
+        assert_eq!(array.dtype(), DType::Bool);
+        assert_eq!(array.sum(), 2.);
+
+        let array: DynArray = ArrayND::new(vec![1u16, 2, 3]).into();
+        assert_eq!(array.dtype(), DType::UInt16);
+        assert_eq!(array, DynArray::UInt16(ArrayND::new(vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn arithmetic_dispatch() {
+        let lhs = DynArray::arange(0., 6., 1., DType::Int32).reshape(&[2, 3]);
+        let rhs = DynArray::from_f64_vec(vec![1., 2., 3.], vec![3], DType::Int32);
+
+        let sum = &lhs + &rhs;
+        assert_eq!(sum.dtype(), DType::Int32);
+        assert_eq!(sum.to_f64_vec(), vec![1., 3., 5., 4., 6., 8.]);
+        assert_eq!((lhs / 2.).to_f64_vec(), vec![0., 0., 1., 1., 2., 2.]);
+
+        let floats = DynArray::from_f64_vec(vec![1., 2.], vec![2], DType::Float32) * 0.5;
+        assert_eq!(floats, DynArray::Float32(ArrayND::new(vec![0.5, 1.])));
+    }
+
+    #[test]
+    fn arithmetic_promotion() {
+        let ints = DynArray::arange(0., 3., 1., DType::Int32);
+        let floats = DynArray::from_f64_vec(vec![0.5; 3], vec![3], DType::Float32);
+        let sum = &ints + &floats;
+        assert_eq!(sum, DynArray::Float64(ArrayND::new(vec![0.5, 1.5, 2.5])));
+
+        let bytes = DynArray::from_f64_vec(vec![200., 255.], vec![2], DType::UInt8);
+        let signed = DynArray::from_f64_vec(vec![-100., 1.], vec![2], DType::Int8);
+        let diff = signed - bytes;
+        assert_eq!(diff, DynArray::Int16(ArrayND::new(vec![-300, -254])));
+
+        let mask = DynArray::from_f64_vec(vec![1., 0.], vec![2], DType::Bool);
+        assert_eq!((mask + diff).dtype(), DType::Int16);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic is not supported for bool arrays")]
+    fn arithmetic_bool() {
+        let mask = DynArray::from_f64_vec(vec![1., 0.], vec![2], DType::Bool);
+        let _ = &mask + &mask;
+    }
+
+    #[test]
+    fn astype() {
+        let array = DynArray::from_f64_vec(vec![1.5, -2.5, 1e10], vec![3], DType::Float64);
+
+        let error = array.astype(DType::Int32, Casting::SameKind).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot cast from Float64 to Int32 with SameKind casting"
+        );
+        assert!(array.astype(DType::Float32, Casting::Safe).is_err());
+        assert_eq!(
+            array.astype(DType::Float32, Casting::SameKind).unwrap(),
+            DynArray::Float32(ArrayND::new(vec![1.5, -2.5, 1e10]))
+        );
+        assert_eq!(
+            array.astype(DType::Int32, Casting::Unsafe).unwrap(),
+            DynArray::Int32(ArrayND::new(vec![1, -2, i32::MAX]))
+        );
+
+        let big = DynArray::Int64(ArrayND::new(vec![i64::MAX, -1]));
+        assert_eq!(
+            big.astype(DType::UInt64, Casting::Unsafe).unwrap(),
+            DynArray::UInt64(ArrayND::new(vec![i64::MAX as u64, u64::MAX]))
+        );
+    }
+
+    #[test]
+    fn reductions() {
+        let array = DynArray::from_f64_vec(vec![3., -1., 4.], vec![3], DType::Int8);
+
+        assert_eq!((array.min(), array.max()), (-1., 4.));
+        assert_eq!(array.sum(), 6.);
+        assert_eq!(array.mean(), 2.);
+    }
+
+    #[test]
+    fn math() {
+        let ints = DynArray::from_f64_vec(vec![-4., 9.], vec![2], DType::Int32);
+        let roots = ints.clone().abs().sqrt();
+        assert_eq!(roots, DynArray::Float64(ArrayND::new(vec![2., 3.])));
+
+        let mut floats = DynArray::from_f64_vec(vec![2., 3.], vec![2], DType::Float32);
+        floats.pow_scalar_mut(2.);
+        assert_eq!(floats, DynArray::Float32(ArrayND::new(vec![4., 9.])));
+        floats.maximum_mut(&DynArray::from_f64_vec(vec![5.], vec![1], DType::Float32));
+        assert_eq!(floats, DynArray::Float32(ArrayND::new(vec![5., 9.])));
+
+        let max = ints.clone().maximum(&floats);
+        assert_eq!(max, DynArray::Float64(ArrayND::new(vec![5., 9.])));
+
+        let mut unchanged = ints.clone();
+        let matrix = DynArray::from_f64_vec(vec![1.; 4], vec![2, 2], DType::Float64);
+        assert!(unchanged.try_pow_mut(&matrix).is_err());
+        assert_eq!(unchanged, ints);
+        assert_eq!(ints.try_pow(&matrix).unwrap().shape(), &[2, 2]);
+    }
+
+    #[test]
+    fn math_keeps_integer_types() {
+        let ints = DynArray::from_f64_vec(vec![-3., 0., 7.], vec![3], DType::Int16);
+        assert_eq!(
+            ints.clone().abs(),
+            DynArray::Int16(ArrayND::new(vec![3, 0, 7]))
+        );
+        assert_eq!(
+            ints.clone().sign(),
+            DynArray::Int16(ArrayND::new(vec![-1, 0, 1]))
+        );
+        for rounded in [
+            ints.clone().floor(),
+            ints.clone().ceil(),
+            ints.clone().round(),
+            ints.clone().trunc(),
+        ] {
+            assert_eq!(rounded, ints);
+        }
+        let mut min = DynArray::Int8(ArrayND::new(vec![i8::MIN, -1]));
+        min.abs_mut();
+        assert_eq!(min, DynArray::Int8(ArrayND::new(vec![i8::MIN, 1])));
+        let unsigned = DynArray::from_f64_vec(vec![0., 5.], vec![2], DType::UInt32);
+        assert_eq!(unsigned.sign().to_f64_vec(), vec![0., 1.]);
+
+        let bools = DynArray::from_f64_vec(vec![1., 0.], vec![2], DType::Bool);
+        assert_eq!(bools.abs().dtype(), DType::Float64);
+        assert_eq!(ints.sqrt().dtype(), DType::Float64);
+    }
+
+    #[test]
+    fn integer_division_errors() {
+        let ints = DynArray::from_f64_vec(vec![4., -6.], vec![2], DType::Int32);
+        assert!(matches!(
+            ints.clone().try_div_scalar(0.),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        let divisors = DynArray::from_f64_vec(vec![2., 0.], vec![2], DType::Int32);
+        assert!(matches!(
+            ints.clone().try_div(&divisors),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        let min = DynArray::from_f64_vec(vec![i64::MIN as f64], vec![1], DType::Int64);
+        assert!(min.try_div_scalar(-1.).is_err());
+
+        let quotient = ints.clone().try_div_scalar(2.).unwrap();
+        assert_eq!(quotient.to_f64_vec(), vec![2., -3.]);
+        let floats = DynArray::from_f64_vec(vec![1.], vec![1], DType::Float64);
+        assert_eq!((floats / 0.).to_f64_vec(), vec![f64::INFINITY]);
+    }
+
+    #[test]
+    fn fractional_scalars_promote() {
+        let ints = DynArray::from_f64_vec(vec![1., 2.], vec![2], DType::Int32);
+        let sum = ints.clone().try_add_scalar(1.5).unwrap();
+        assert_eq!(sum.dtype(), DType::Float64);
+        assert_eq!(sum.to_f64_vec(), vec![2.5, 3.5]);
+        assert_eq!((ints.clone() / 0.5).to_f64_vec(), vec![2., 4.]);
+        assert_eq!((ints.clone() * f64::INFINITY).dtype(), DType::Float64);
+
+        let whole = ints.clone() + 2.;
+        assert_eq!(whole.dtype(), DType::Int32);
+        assert_eq!(whole.to_f64_vec(), vec![3., 4.]);
+        let floats = DynArray::from_f64_vec(vec![1.], vec![1], DType::Float32);
+        assert_eq!((floats + 0.5).dtype(), DType::Float32);
+    }
+
+    #[test]
+    fn out_of_range_scalars_promote() {
+        let uint8 = DynArray::from_f64_vec(vec![5.], vec![1], DType::UInt8);
+        let difference = uint8.clone() + -1.;
+        assert_eq!(difference.dtype(), DType::Int16);
+        assert_eq!(difference.to_f64_vec(), vec![4.]);
+        assert_eq!((uint8.clone() * -2.).to_f64_vec(), vec![-10.]);
+        assert_eq!((uint8.clone() + 300.).dtype(), DType::UInt16);
+        assert_eq!((uint8 + 255.).dtype(), DType::UInt8);
+
+        let int8 = DynArray::from_f64_vec(vec![1.], vec![1], DType::Int8);
+        let sum = int8.clone() + 1000.;
+        assert_eq!(sum.dtype(), DType::Int32);
+        assert_eq!(sum.to_f64_vec(), vec![1001.]);
+        assert_eq!((int8 + -128.).dtype(), DType::Int8);
+        let uint64 = DynArray::from_f64_vec(vec![1.], vec![1], DType::UInt64);
+        assert_eq!((uint64.clone() - 1.).dtype(), DType::UInt64);
+        assert_eq!((uint64 + -1.).dtype(), DType::Float64);
+    }
+
+    #[test]
+    fn sum_small_integers() {
+        let int8 = DynArray::from_f64_vec(vec![100., 100.], vec![2], DType::Int8);
+        assert_eq!(int8.sum(), 200.);
+        assert_eq!(int8.nansum(), 200.);
+        assert_eq!(int8.mean(), 100.);
+        let uint8 = DynArray::from_f64_vec(vec![200., 250., 255.], vec![3], DType::UInt8);
+        assert_eq!(uint8.sum(), 705.);
+        assert_eq!(uint8.mean(), 235.);
+    }
+
+    #[test]
+    fn small_integers_wrap() {
+        let int8 = DynArray::from_f64_vec(vec![100.], vec![1], DType::Int8);
+        let sum = int8.clone() + 100.;
+        assert_eq!(sum.dtype(), DType::Int8);
+        assert_eq!(sum.to_f64_vec(), vec![-56.]);
+        assert_eq!((int8.clone() + int8.clone()).to_f64_vec(), vec![-56.]);
+        assert_eq!((int8 * 3.).to_f64_vec(), vec![44.]);
+        let uint8 = DynArray::from_f64_vec(vec![1.], vec![1], DType::UInt8);
+        assert_eq!((uint8 - 2.).to_f64_vec(), vec![255.]);
+    }
+
+    #[test]
+    fn try_errors() {
+        let ints = DynArray::arange(0., 3., 1., DType::Int32);
+        let mask = DynArray::from_f64_vec(vec![1., 0.], vec![2], DType::Bool);
+
+        assert!(matches!(
+            ints.clone().try_add(&mask),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            mask.try_mul_scalar(2.),
+            Err(NumrsError::UnsupportedDType(_))
+        ));
+        assert!(matches!(
+            DynArray::from_f64_vec(vec![], vec![0], DType::Int8).try_max(),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+        assert!(ints.try_reshape(&[2, -1]).is_err());
+    }
+}
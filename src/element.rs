@@ -0,0 +1,239 @@
+//! Element types that can be stored in an `ArrayND`
+
+use std::fmt::{Debug, Display};
+
+use crate::DType;
+
+/// Types that can be stored in an `ArrayND`
+///
+/// Implemented for `f32`, `f64`, the signed and unsigned integers from 8 to
+/// 64 bits, and `bool`. Arithmetic operators are available whenever the
+/// element type itself supports them, so `bool` Arrays can be compared and
+/// reduced with `min`/`max` but not added. Integer `+`, `-` and `*` wrap
+/// around on overflow, in debug and release builds alike.
+pub trait Element: Copy + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static {
+    /// Runtime tag of the type, used by `DynArray`
+    const DTYPE: DType;
+
+    /// Additive identity, `false` for `bool`
+    fn zero() -> Self;
+
+    /// Multiplicative identity, `true` for `bool`
+    fn one() -> Self;
+
+    /// Whether the value is NaN, always false for non-float types
+    fn is_nan(self) -> bool {
+        false
+    }
+
+    /// NaN for float types, which is what reductions over no elements return
+    fn nan() -> Option<Self> {
+        None
+    }
+
+    /// Converts to `f64`, which may lose precision for 64 bit integers
+    fn to_f64(self) -> f64;
+
+    /// Converts from `f64` like an `as` cast, so integers truncate and saturate
+    /// and `bool` is true for anything non-zero
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts to `i128`, which holds every integer value exactly
+    fn to_i128(self) -> i128;
+
+    /// Converts from `i128` like an `as` cast, wrapping for integer types
+    fn from_i128(value: i128) -> Self;
+
+    /// `self + rhs`, wrapping around on overflow for integer types
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    /// `self - rhs`, wrapping around on overflow for integer types
+    fn wrapping_sub(self, rhs: Self) -> Self;
+
+    /// `self * rhs`, wrapping around on overflow for integer types
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    /// `self / rhs`, `None` if an integer division has a zero divisor or
+    /// overflows like `i32::MIN / -1`. Always `Some` for float types.
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+
+    /// Converts to another element type like an `as` cast
+    ///
+    /// Values that fit the target type are preserved exactly, apart from
+    /// rounding when converting to a float type.
+    fn cast<U: Element>(self) -> U {
+        if Self::DTYPE.is_float() || U::DTYPE.is_float() {
+            U::from_f64(self.to_f64())
+        } else {
+            U::from_i128(self.to_i128())
+        }
+    }
+}
+
+macro_rules! impl_element_int {
+    ($($t:ty => $dtype:ident),*) => {
+        $(
+            impl Element for $t {
+                const DTYPE: DType = DType::$dtype;
+
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Self {
+                    value as $t
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_div(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_element_float {
+    ($($t:ident => $dtype:ident),*) => {
+        $(
+            impl Element for $t {
+                const DTYPE: DType = DType::$dtype;
+
+                fn zero() -> Self {
+                    0.
+                }
+
+                fn one() -> Self {
+                    1.
+                }
+
+                fn is_nan(self) -> bool {
+                    $t::is_nan(self)
+                }
+
+                fn nan() -> Option<Self> {
+                    Some($t::NAN)
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Self {
+                    value as $t
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    self + rhs
+                }
+
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    self - rhs
+                }
+
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    self * rhs
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    Some(self / rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_element_int!(
+    i8 => Int8,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    u8 => UInt8,
+    u16 => UInt16,
+    u32 => UInt32,
+    u64 => UInt64
+);
+impl_element_float!(f32 => Float32, f64 => Float64);
+
+impl Element for bool {
+    const DTYPE: DType = DType::Bool;
+
+    fn zero() -> Self {
+        false
+    }
+
+    fn one() -> Self {
+        true
+    }
+
+    fn to_f64(self) -> f64 {
+        self as u8 as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value != 0.
+    }
+
+    fn to_i128(self) -> i128 {
+        self as i128
+    }
+
+    fn from_i128(value: i128) -> Self {
+        value != 0
+    }
+
+    /// Logical or, like adding NumPy bools
+    fn wrapping_add(self, rhs: Self) -> Self {
+        self | rhs
+    }
+
+    /// Logical xor, the difference modulo 2
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        self ^ rhs
+    }
+
+    /// Logical and, like multiplying NumPy bools
+    fn wrapping_mul(self, rhs: Self) -> Self {
+        self & rhs
+    }
+
+    /// `self`, `None` when dividing by `false` like dividing by zero
+    fn checked_div(self, rhs: Self) -> Option<Self> {
+        rhs.then_some(self)
+    }
+}
@@ -0,0 +1,77 @@
+//! Errors returned by the fallible `try_` methods
+//!
+//! Every method that can panic has a `try_` counterpart returning a
+//! [`NumrsError`], and the panicking version panics with the same message.
+//! The release profile aborts on panic, so code that can hit these errors,
+//! like the wasm bindings, should use the `try_` methods.
+
+use std::fmt::Display;
+
+use crate::{Casting, DType};
+
+/// Result of a fallible numrs operation
+pub type Result<T> = std::result::Result<T, NumrsError>;
+
+#[derive(Debug)]
+pub enum NumrsError {
+    /// Shapes that cannot be broadcast together, reshaped into each other or
+    /// filled from the given number of elements
+    ShapeMismatch(String),
+    /// An index or axis outside the bounds of the Array
+    IndexOutOfBounds(String),
+    /// A reduction without an identity, like the minimum of an empty integer Array
+    EmptyReduction(String),
+    /// A cast that is not allowed under the requested `Casting` policy
+    InvalidCast {
+        from: DType,
+        to: DType,
+        casting: Casting,
+    },
+    /// An operation that is not defined for the element type, like adding bool Arrays
+    UnsupportedDType(String),
+    /// Integer arithmetic without a representable result, like division by zero
+    Arithmetic(String),
+    /// A thread pool for multi-threaded work could not be created
+    ThreadPool(String),
+    /// Reading or writing an Array failed
+    Io(std::io::Error),
+}
+
+impl Display for NumrsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumrsError::ShapeMismatch(message)
+            | NumrsError::IndexOutOfBounds(message)
+            | NumrsError::EmptyReduction(message)
+            | NumrsError::UnsupportedDType(message)
+            | NumrsError::Arithmetic(message)
+            | NumrsError::ThreadPool(message) => write!(f, "{}", message),
+            NumrsError::InvalidCast { from, to, casting } => write!(
+                f,
+                "cannot cast from {:?} to {:?} with {:?} casting",
+                from, to, casting
+            ),
+            NumrsError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for NumrsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NumrsError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for NumrsError {
+    fn from(error: std::io::Error) -> NumrsError {
+        NumrsError::Io(error)
+    }
+}
+
+/// Unwraps the result of a `try_` method for its panicking counterpart
+pub(crate) fn or_panic<T>(result: Result<T>) -> T {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
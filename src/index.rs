@@ -0,0 +1,153 @@
+//! Element access with `[]` and the checked `get`/`get_mut`
+//!
+//! An element is addressed either by a full multi-index like `[1, 2]`, or by a
+//! single `usize` counting elements in row-major order, so `array[i]` is the
+//! same element as `array.to_vec()[i]` whatever the memory layout.
+
+use std::ops::{Index, IndexMut};
+
+use crate::{error, layout, ArrayND, Element, Result};
+
+/// Types that address a single element of an Array
+///
+/// Implemented for `usize` (position in row-major order), `[usize; N]` and
+/// `&[usize]` (one index per axis).
+pub trait NdIndex {
+    /// Position of the element in a buffer starting at `start` laid out with
+    /// `shape` and `strides`, failing if it is out of bounds
+    fn offset(&self, start: usize, shape: &[usize], strides: &[isize]) -> Result<usize>;
+}
+
+impl NdIndex for usize {
+    fn offset(&self, start: usize, shape: &[usize], strides: &[isize]) -> Result<usize> {
+        layout::flat_offset(start, shape, strides, *self)
+    }
+}
+
+impl<const N: usize> NdIndex for [usize; N] {
+    fn offset(&self, start: usize, shape: &[usize], strides: &[isize]) -> Result<usize> {
+        layout::offset_of(start, shape, strides, self)
+    }
+}
+
+impl NdIndex for &[usize] {
+    fn offset(&self, start: usize, shape: &[usize], strides: &[isize]) -> Result<usize> {
+        layout::offset_of(start, shape, strides, self)
+    }
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Reference to the element at `index`, or `None` if it is out of bounds
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+    /// assert_eq!(array.get([1, 2]), Some(&6.0));
+    /// assert_eq!(array.get(4), Some(&5.0));
+    /// assert_eq!(array.get([2, 0]), None);
+    /// ```
+    pub fn get<I: NdIndex>(&self, index: I) -> Option<&T> {
+        let offset = index.offset(0, &self.shape, &self.strides).ok()?;
+        Some(&self.data[offset])
+    }
+
+    /// Mutable reference to the element at `index`, or `None` if it is out of bounds
+    pub fn get_mut<I: NdIndex>(&mut self, index: I) -> Option<&mut T> {
+        let offset = index.offset(0, &self.shape, &self.strides).ok()?;
+        self.invalidate_stats();
+        Some(&mut self.data[offset])
+    }
+}
+
+/// Panics if the index is out of bounds
+///
+/// # Example
+/// ```
+/// use numrs::ArrayND;
+/// let mut array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+/// assert_eq!(array[[1, 0]], 3.0);
+/// array[3] = 10.0;
+/// assert_eq!(array.to_vec(), vec![1.0, 2.0, 3.0, 10.0]);
+/// ```
+impl<T: Element, I: NdIndex> Index<I> for ArrayND<T> {
+    type Output = T;
+
+    fn index(&self, index: I) -> &T {
+        &self.data[error::or_panic(index.offset(0, &self.shape, &self.strides))]
+    }
+}
+
+impl<T: Element, I: NdIndex> IndexMut<I> for ArrayND<T> {
+    fn index_mut(&mut self, index: I) -> &mut T {
+        let offset = error::or_panic(index.offset(0, &self.shape, &self.strides));
+        self.invalidate_stats();
+        &mut self.data[offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{s, ArrayND};
+
+    fn grid() -> ArrayND<i32> {
+        ArrayND::from_shape_vec((0..6).collect(), vec![2, 3])
+    }
+
+    #[test]
+    fn index_multi() {
+        let array = grid();
+        assert_eq!(array[[0, 0]], 0);
+        assert_eq!(array[[1, 2]], 5);
+        assert_eq!(array[&[1, 1][..]], 4);
+    }
+
+    #[test]
+    fn index_flat_follows_logical_order() {
+        let array = grid().transpose();
+        assert_eq!(array.shape(), &[3, 2]);
+        let flat: Vec<i32> = (0..6).map(|i| array[i]).collect();
+        assert_eq!(flat, array.to_vec());
+        assert_eq!(array[[2, 1]], 5);
+    }
+
+    #[test]
+    fn index_mut_updates_stats() {
+        let mut array = grid();
+        assert_eq!(array.max(), 5);
+        array[[0, 1]] = 10;
+        assert_eq!(array.max(), 10);
+        *array.get_mut(5).unwrap() = -1;
+        assert_eq!(array.min(), -1);
+    }
+
+    #[test]
+    fn get_out_of_bounds() {
+        let mut array = grid();
+        assert_eq!(array.get([1, 3]), None);
+        assert_eq!(array.get([0, 0, 0]), None);
+        assert_eq!(array.get(6), None);
+        assert!(array.get_mut([2, 0]).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn index_out_of_bounds() {
+        let array = grid();
+        let _ = array[[2, 0]];
+    }
+
+    #[test]
+    fn index_views() {
+        let mut array = grid();
+        let view = array.slice(s![.., 1..]);
+        assert_eq!(view[[1, 0]], 4);
+        assert_eq!(view[3], 5);
+        assert_eq!(view.get([0, 2]), None);
+
+        let mut view = array.slice_mut(s![1, ..]);
+        view[0] = 30;
+        *view.get_mut([2]).unwrap() = 50;
+        assert_eq!(array.to_vec(), vec![0, 1, 2, 30, 4, 50]);
+    }
+}
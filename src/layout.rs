@@ -0,0 +1,346 @@
+//! Helpers for mapping multi-indices onto a flat buffer
+
+use std::ops::Range;
+
+use crate::{NumrsError, Result};
+
+/// Strides for a contiguous row-major (C order) buffer of the given shape
+pub(crate) fn row_major_strides(shape: &[usize]) -> Vec<isize> {
+    let mut strides = vec![0; shape.len()];
+    let mut stride: isize = 1;
+    for (axis, &len) in shape.iter().enumerate().rev() {
+        strides[axis] = stride;
+        stride *= len.max(1) as isize;
+    }
+    strides
+}
+
+/// Position in the buffer of the element at `index`, for an array starting at `start`
+///
+/// Fails if the index has the wrong number of axes or is out of bounds.
+pub(crate) fn offset_of(
+    start: usize,
+    shape: &[usize],
+    strides: &[isize],
+    index: &[usize],
+) -> Result<usize> {
+    if index.len() != shape.len() {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "index {:?} does not match array of {} dimensions",
+            index,
+            shape.len()
+        )));
+    }
+    let mut offset = start as isize;
+    for ((&i, &len), &stride) in index.iter().zip(shape).zip(strides) {
+        if i >= len {
+            return Err(NumrsError::IndexOutOfBounds(format!(
+                "index {:?} is out of bounds for array of shape {:?}",
+                index, shape
+            )));
+        }
+        offset += i as isize * stride;
+    }
+    Ok(offset as usize)
+}
+
+/// Position in the buffer of the `flat`-th element in row-major order, for an
+/// array starting at `start`
+///
+/// Fails if `flat` is not smaller than the number of elements.
+pub(crate) fn flat_offset(
+    start: usize,
+    shape: &[usize],
+    strides: &[isize],
+    flat: usize,
+) -> Result<usize> {
+    let size: usize = shape.iter().product();
+    if flat >= size {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "index {} is out of bounds for array of size {}",
+            flat, size
+        )));
+    }
+    let mut rest = flat;
+    let mut offset = start as isize;
+    for (&len, &stride) in shape.iter().zip(strides).rev() {
+        offset += (rest % len) as isize * stride;
+        rest /= len;
+    }
+    Ok(offset as usize)
+}
+
+/// Whether `strides` describe a contiguous row-major buffer for `shape`
+///
+/// Axes of length 1 are ignored since their stride is never used.
+pub(crate) fn is_row_major(shape: &[usize], strides: &[isize]) -> bool {
+    if shape.contains(&0) {
+        return true;
+    }
+    let expected = row_major_strides(shape);
+    shape
+        .iter()
+        .zip(strides.iter().zip(expected))
+        .all(|(&len, (&stride, expected))| len == 1 || stride == expected)
+}
+
+/// Resolves a requested shape which may contain a single `-1` into a concrete shape
+///
+/// Fails if more than one axis is `-1`, an axis is otherwise negative, or the
+/// shape does not hold exactly `size` elements.
+pub(crate) fn infer_shape(size: usize, shape: &[isize]) -> Result<Vec<usize>> {
+    let mismatch = || {
+        NumrsError::ShapeMismatch(format!(
+            "cannot reshape array of size {} into shape {:?}",
+            size, shape
+        ))
+    };
+    let mut inferred: Option<usize> = None;
+    let mut known: usize = 1;
+    for (axis, &len) in shape.iter().enumerate() {
+        if len == -1 {
+            if inferred.is_some() {
+                return Err(NumrsError::ShapeMismatch(format!(
+                    "can only infer one axis of {:?}",
+                    shape
+                )));
+            }
+            inferred = Some(axis);
+        } else if len < 0 {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "invalid axis length {} in {:?}",
+                len, shape
+            )));
+        } else {
+            known *= len as usize;
+        }
+    }
+    let mut resolved: Vec<usize> = shape.iter().map(|&len| len.max(0) as usize).collect();
+    if let Some(axis) = inferred {
+        if known == 0 || !size.is_multiple_of(known) {
+            return Err(mismatch());
+        }
+        resolved[axis] = size / known;
+    }
+    if resolved.iter().product::<usize>() != size {
+        return Err(mismatch());
+    }
+    Ok(resolved)
+}
+
+/// Shape and strides with the axes reordered so that axis `i` becomes axis `axes[i]`
+///
+/// Fails if `axes` is not a permutation of `0..shape.len()`.
+pub(crate) fn permute(
+    shape: &[usize],
+    strides: &[isize],
+    axes: &[usize],
+) -> Result<(Vec<usize>, Vec<isize>)> {
+    let mut seen = vec![false; shape.len()];
+    if axes.len() != shape.len() {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "axes {:?} do not match array of {} dimensions",
+            axes,
+            shape.len()
+        )));
+    }
+    for &axis in axes {
+        if axis >= shape.len() || seen[axis] {
+            return Err(NumrsError::IndexOutOfBounds(format!(
+                "axes {:?} are not a permutation",
+                axes
+            )));
+        }
+        seen[axis] = true;
+    }
+    Ok((
+        axes.iter().map(|&axis| shape[axis]).collect(),
+        axes.iter().map(|&axis| strides[axis]).collect(),
+    ))
+}
+
+/// Fails unless `axis` is an axis of an array with `ndim` dimensions
+pub(crate) fn check_axis(axis: usize, ndim: usize) -> Result<()> {
+    if axis >= ndim {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "axis {} is out of bounds for array of {} dimensions",
+            axis, ndim
+        )));
+    }
+    Ok(())
+}
+
+/// Restricts `axis` to the positions in `range`, returning the new start offset
+///
+/// Fails if the axis or range is out of bounds.
+pub(crate) fn slice_axis(
+    offset: usize,
+    shape: &mut [usize],
+    strides: &[isize],
+    axis: usize,
+    range: Range<usize>,
+) -> Result<usize> {
+    check_axis(axis, shape.len())?;
+    if range.start > range.end || range.end > shape[axis] {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "range {:?} is out of bounds for axis {} of length {}",
+            range, axis, shape[axis]
+        )));
+    }
+    shape[axis] = range.end - range.start;
+    if shape[axis] == 0 {
+        return Ok(offset);
+    }
+    Ok((offset as isize + range.start as isize * strides[axis]) as usize)
+}
+
+/// Fixes `axis` at `index` and removes it, returning the new start offset
+///
+/// Fails if the axis or index is out of bounds.
+pub(crate) fn index_axis(
+    offset: usize,
+    shape: &mut Vec<usize>,
+    strides: &mut Vec<isize>,
+    axis: usize,
+    index: usize,
+) -> Result<usize> {
+    check_axis(axis, shape.len())?;
+    if index >= shape[axis] {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "index {} is out of bounds for axis {} of length {}",
+            index, axis, shape[axis]
+        )));
+    }
+    shape.remove(axis);
+    let stride = strides.remove(axis);
+    Ok((offset as isize + index as isize * stride) as usize)
+}
+
+/// Iterator over the buffer offsets of every element, in row-major logical order
+pub(crate) struct Offsets<'a> {
+    shape: &'a [usize],
+    strides: &'a [isize],
+    index: Vec<usize>,
+    offset: isize,
+    remaining: usize,
+}
+
+impl<'a> Offsets<'a> {
+    pub(crate) fn new(start: usize, shape: &'a [usize], strides: &'a [isize]) -> Offsets<'a> {
+        Offsets {
+            shape,
+            strides,
+            index: vec![0; shape.len()],
+            offset: start as isize,
+            remaining: shape.iter().product(),
+        }
+    }
+}
+
+impl<'a> Iterator for Offsets<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let current = self.offset as usize;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            for axis in (0..self.shape.len()).rev() {
+                self.index[axis] += 1;
+                self.offset += self.strides[axis];
+                if self.index[axis] < self.shape[axis] {
+                    break;
+                }
+                self.offset -= self.strides[axis] * self.shape[axis] as isize;
+                self.index[axis] = 0;
+            }
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Offsets<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{flat_offset, infer_shape, is_row_major, offset_of, row_major_strides, Offsets};
+    use crate::NumrsError;
+
+    #[test]
+    fn strides() {
+        assert_eq!(row_major_strides(&[2, 3, 4]), vec![12, 4, 1]);
+        assert_eq!(row_major_strides(&[5]), vec![1]);
+        assert_eq!(row_major_strides(&[]), Vec::<isize>::new());
+    }
+
+    #[test]
+    fn offsets() {
+        let strides = row_major_strides(&[2, 3]);
+        assert_eq!(offset_of(0, &[2, 3], &strides, &[0, 0]).unwrap(), 0);
+        assert_eq!(offset_of(0, &[2, 3], &strides, &[1, 2]).unwrap(), 5);
+        assert_eq!(offset_of(4, &[2, 3], &strides, &[1, 2]).unwrap(), 9);
+    }
+
+    #[test]
+    fn flat_offsets() {
+        assert_eq!(flat_offset(0, &[2, 3], &[3, 1], 4).unwrap(), 4);
+        assert_eq!(flat_offset(0, &[2, 3], &[1, 2], 4).unwrap(), 3);
+        assert_eq!(flat_offset(2, &[3], &[-1], 2).unwrap(), 0);
+        assert!(matches!(
+            flat_offset(0, &[2, 3], &[3, 1], 6),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn offset_out_of_bounds() {
+        assert!(matches!(
+            offset_of(0, &[2, 3], &[3, 1], &[0, 3]),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn row_major_check() {
+        assert!(is_row_major(&[2, 3], &[3, 1]));
+        assert!(is_row_major(&[1, 3], &[7, 1]));
+        assert!(!is_row_major(&[2, 3], &[1, 2]));
+    }
+
+    #[test]
+    fn inferred_shape() {
+        assert_eq!(infer_shape(12, &[3, -1]).unwrap(), vec![3, 4]);
+        assert_eq!(infer_shape(12, &[-1]).unwrap(), vec![12]);
+        assert_eq!(infer_shape(12, &[2, 2, 3]).unwrap(), vec![2, 2, 3]);
+    }
+
+    #[test]
+    fn inferred_shape_two_unknowns() {
+        assert!(matches!(
+            infer_shape(12, &[-1, -1]),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn inferred_shape_indivisible() {
+        assert!(matches!(
+            infer_shape(12, &[5, -1]),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn offsets_transposed() {
+        let offsets: Vec<usize> = Offsets::new(0, &[3, 2], &[1, 3]).collect();
+        assert_eq!(offsets, vec![0, 3, 1, 4, 2, 5]);
+        let offsets: Vec<usize> = Offsets::new(2, &[2], &[-1]).collect();
+        assert_eq!(offsets, vec![2, 1]);
+    }
+}
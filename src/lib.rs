@@ -1,38 +1,85 @@
-use num_traits::Zero;
-use rand::Rng;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rand::{
+    distributions::{uniform::SampleUniform, Standard},
+    prelude::Distribution,
+    rngs::ThreadRng,
+    Rng,
+};
+#[cfg(target_family = "unix")]
+use rayon::{
+    iter::ParallelIterator,
+    slice::{ParallelSlice, ParallelSliceMut},
+};
 use std::{
+    borrow::Cow,
     cmp::min,
     fmt::{Debug, Display},
-    ops::{Add, Div, Mul, Sub},
+    ops::Add,
+    sync::OnceLock,
 };
-use wasm_bindgen::prelude::wasm_bindgen;
 
+mod broadcast;
+mod dtype;
+mod dynarray;
+mod element;
+mod error;
+mod index;
+mod layout;
+mod mask;
+mod math;
+mod ops;
+mod parallel;
+mod reduce;
+mod simd;
+mod slice;
+mod summation;
+mod take;
+mod ufunc;
+mod view;
+mod wasm;
+
+pub use dtype::{Casting, DType};
+pub use dynarray::DynArray;
+pub use element::Element;
+pub use error::{NumrsError, Result};
+pub use index::NdIndex;
+pub use parallel::{parallelism, set_parallelism, with_parallelism, Parallelism};
+pub use slice::{NewAxis, SliceElem};
+pub use summation::SumMode;
+pub use take::IndexMode;
+pub use ufunc::{
+    BinaryOp, BinaryUfunc, Builtin, NumericBinaryOp, NumericUnaryOp, UnaryOp, UnaryUfunc,
+};
+pub use view::{ArrayView, ArrayViewMut};
 
-/// 1D Array
+/// N-dimensional Array
 ///
 ///
-/// Uses a Vec internally.
+/// Uses a Vec internally, laid out in row-major order.
 /// Takes ownership of the data
 ///
+/// The element type defaults to `f64`, see [`Element`] for the supported types.
+///
 /// # Example
 /// ```
 /// use numrs::ArrayND;
-/// let data: Vec<f64> = vec![1.0, 2.0, 3.0];
-/// let array: ArrayND = ArrayND::new(data);
-/// ``` 
-#[wasm_bindgen]
+/// let data: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+/// let array: ArrayND = ArrayND::from_shape_vec(data, vec![2, 3]);
+/// assert_eq!(array.shape(), &[2, 3]);
+///
+/// let integers: ArrayND<i32> = ArrayND::new(vec![1, 2, 3]);
+/// assert_eq!(integers.sum(), 6);
+/// ```
 #[derive(Clone)]
-pub struct ArrayND {
-    data: Vec<f64>,
-    pub max: f64,
-    pub min: f64,
+pub struct ArrayND<T = f64> {
+    data: Vec<T>,
+    /// Lazily computed (min, max), cleared whenever the data is mutated
+    stats: OnceLock<(T, T)>,
     shape: Vec<usize>,
+    strides: Vec<isize>,
     size: usize,
 }
 
-#[wasm_bindgen]
-impl ArrayND {
+impl<T: Element> ArrayND<T> {
     /// Creates a new 1D Array
     ///
     /// # Example
@@ -41,539 +88,665 @@ impl ArrayND {
     /// let data: Vec<f64> = vec![1.0, 2.0, 3.0];
     /// let array: ArrayND = ArrayND::new(data);
     /// ```
-    #[wasm_bindgen(constructor)]
-    pub fn new(data: Vec<f64>) -> ArrayND {
-        let min: f64 = find_min::<f64>(&data);
-        let max: f64 = find_max::<f64>(&data);
+    pub fn new(data: Vec<T>) -> ArrayND<T> {
+        let shape = vec![data.len()];
+        ArrayND::from_shape_vec(data, shape)
+    }
 
-        ArrayND {
-            shape: vec![data.len()],
-            size: data.len(),
-            data,
-            min,
-            max,
-        }
+    /// Creates a new Array with the given shape
+    ///
+    /// The data is read in row-major order, so the last axis varies fastest.
+    /// Panics if the number of elements does not match the shape.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+    /// assert_eq!(array.ndim(), 2);
+    /// assert_eq!(array.item(&[1, 0]), 3.0);
+    /// ```
+    pub fn from_shape_vec(data: Vec<T>, shape: Vec<usize>) -> ArrayND<T> {
+        error::or_panic(ArrayND::try_from_shape_vec(data, shape))
     }
 
-    /// Sums the data inside 1D Array
+    /// Creates a new Array with the given shape, failing if the number of
+    /// elements does not match it
     ///
-    /// Uses a sequential sum when the Array size is small (less than 1 million)
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, NumrsError};
+    /// let result: Result<ArrayND, _> = ArrayND::try_from_shape_vec(vec![1.0, 2.0, 3.0], vec![2, 2]);
+    /// assert!(matches!(result, Err(NumrsError::ShapeMismatch(_))));
+    /// ```
+    pub fn try_from_shape_vec(data: Vec<T>, shape: Vec<usize>) -> Result<ArrayND<T>> {
+        let size: usize = shape.iter().product();
+        if size != data.len() {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "cannot create an array of shape {:?} from {} elements",
+                shape,
+                data.len()
+            )));
+        }
+        Ok(ArrayND {
+            strides: layout::row_major_strides(&shape),
+            shape,
+            size,
+            data,
+            stats: OnceLock::new(),
+        })
+    }
+
+    /// Number of axes of the Array
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Smallest element of the Array
     ///
-    /// Uses a parallel sum when the Array size is large (greater than 1 million)
+    /// Computed on first use and cached until the Array is mutated.
+    /// NaN if the Array is empty or contains a NaN. Panics if a non-float
+    /// Array is empty.
     ///
     /// # Example
     /// ```
     /// use numrs::ArrayND;
-    /// let data: Vec<f64> = vec![1.0, 2.0, 3.0];
-    /// let array: ArrayND = ArrayND::new(data);
-    /// assert_eq!(array.sum(), 6.0);
+    /// let array: ArrayND = ArrayND::new(vec![3.0, -1.0, 2.0]);
+    /// assert_eq!(array.min(), -1.0);
+    /// assert_eq!((array * -2.0).min(), -6.0);
     /// ```
-    pub fn sum(&self) -> f64 {
-        if self.size > 1_000_000 {
-            self.par_sum()
-        } else {
-            self.seq_sum()
-        }
+    pub fn min(&self) -> T {
+        error::or_panic(self.try_min())
     }
 
-    /// Seqential Sum used inside 1D Array
-    pub fn seq_sum(&self) -> f64 {
-        self.data.iter().fold(0., |sum, &val| sum + val)
+    /// Smallest element of the Array, failing instead of panicking if a
+    /// non-float Array is empty
+    pub fn try_min(&self) -> Result<T> {
+        self.try_stats().map(|stats| stats.0)
     }
 
-    #[cfg(target_family = "wasm")]
-    /// Seqential Sum used inside 1D Array
-    pub fn par_sum(&self) -> f64 {
-        self.seq_sum()
+    /// Largest element of the Array
+    ///
+    /// Computed on first use and cached until the Array is mutated.
+    /// NaN if the Array is empty or contains a NaN. Panics if a non-float
+    /// Array is empty.
+    pub fn max(&self) -> T {
+        error::or_panic(self.try_max())
     }
 
-    #[cfg(target_family = "unix")]
-    /// Parallel Sum used inside 1D Array
-    pub fn par_sum(&self) -> f64 {
-        self.data
-            .par_iter()
-            .fold(|| 0., |sum, &val| sum + val)
-            .collect::<Vec<f64>>()
-            .iter()
-            .fold(0., |sum, &val| sum + val)
+    /// Largest element of the Array, failing instead of panicking if a
+    /// non-float Array is empty
+    pub fn try_max(&self) -> Result<T> {
+        self.try_stats().map(|stats| stats.1)
     }
 
-    /// Generates a random 1D Array
+    /// Smallest element of the Array, ignoring NaN
+    ///
+    /// NaN if the Array is empty or only contains NaN.
     ///
     /// # Example
     /// ```
     /// use numrs::ArrayND;
-    /// let array: ArrayND = ArrayND::random(10);
+    /// let array: ArrayND = ArrayND::new(vec![3.0, f64::NAN, 2.0]);
+    /// assert!(array.min().is_nan());
+    /// assert_eq!(array.nanmin(), 2.0);
     /// ```
-    pub fn random(size: usize) -> ArrayND {
-        let mut rng = rand::thread_rng();
-        let data: Vec<f64> = (0..size).map(|_| rng.gen::<f64>()).collect();
-        ArrayND::new(data)
+    pub fn nanmin(&self) -> T {
+        error::or_panic(self.try_nanmin())
     }
 
-    /// Generates a random 1D Array with a range
+    /// Smallest element of the Array ignoring NaN, failing instead of
+    /// panicking if a non-float Array is empty
+    pub fn try_nanmin(&self) -> Result<T> {
+        self.min_max(true)
+            .map_or_else(empty_min_max, Ok)
+            .map(|stats| stats.0)
+    }
+
+    /// Largest element of the Array, ignoring NaN
+    ///
+    /// NaN if the Array is empty or only contains NaN.
+    pub fn nanmax(&self) -> T {
+        error::or_panic(self.try_nanmax())
+    }
+
+    /// Largest element of the Array ignoring NaN, failing instead of
+    /// panicking if a non-float Array is empty
+    pub fn try_nanmax(&self) -> Result<T> {
+        self.min_max(true)
+            .map_or_else(empty_min_max, Ok)
+            .map(|stats| stats.1)
+    }
+
+    /// Returns a 1D copy of the Array in row-major order
     ///
     /// # Example
     /// ```
     /// use numrs::ArrayND;
-    /// let array: ArrayND = ArrayND::random_range(10, 1., 10.);
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+    /// assert_eq!(array.transpose().flatten(), ArrayND::new(vec![1.0, 3.0, 2.0, 4.0]));
     /// ```
-    pub fn random_range(size: usize, min: f64, max: f64) -> ArrayND {
-        let mut rng = rand::thread_rng();
-        let data: Vec<f64> = (0..size).map(|_| rng.gen_range(min..max)).collect();
-        ArrayND::new(data)
+    pub fn flatten(&self) -> ArrayND<T> {
+        ArrayND::new(self.to_vec())
     }
 
-    pub fn add(mut self, num: f64) -> ArrayND {
-        self.data.iter_mut().for_each(|x| *x += num);
-        self
+    /// Converts the Array into 1D, only copying the data if it is not contiguous
+    pub fn ravel(self) -> ArrayND<T> {
+        self.reshape(&[-1])
     }
 
+    /// Reverses the order of the axes
+    ///
+    /// Only the strides are permuted, the data is not moved.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+    /// let transposed = array.transpose();
+    /// assert_eq!(transposed.shape(), &[3, 2]);
+    /// assert_eq!(transposed.item(&[2, 0]), 3.0);
+    /// ```
+    pub fn transpose(self) -> ArrayND<T> {
+        let axes: Vec<usize> = (0..self.ndim()).rev().collect();
+        self.permute_axes(&axes)
+    }
+
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
         let mut string = String::new();
-        for i in 0..self.size {
-            string.push_str(&format!("{} ", self.data[i]));
+        for el in self.iter() {
+            string.push_str(&format!("{} ", el));
         }
         string
     }
 
-    pub fn arange(start: f64, stop: f64, step: f64) -> ArrayND {
-        let mut data: Vec<f64> = Vec::new();
-        let mut i = start;
-        while i < stop {
-            data.push(i);
-            i += step;
-        }
-        ArrayND::new(data)
+    /// Length of each axis of the Array
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
     }
-}
 
-#[wasm_bindgen]
-pub fn asarray(data: Vec<f64>) -> ArrayND {
-    ArrayND::new(data)
-}
+    /// Number of elements to step over in the data to move one position along each axis
+    pub fn strides(&self) -> &[isize] {
+        &self.strides
+    }
 
-fn find_min<T: Copy + Zero + std::cmp::PartialOrd>(data: &[f64]) -> f64 {
-    data.iter()
-        .reduce(|x, y| if x < y { x } else { y })
-        .cloned()
-        .unwrap()
-}
+    /// Returns the element at the given multi-index
+    ///
+    /// Panics if the index has the wrong number of axes or is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+    /// assert_eq!(array.item(&[1, 2]), 6.0);
+    /// ```
+    pub fn item(&self, index: &[usize]) -> T {
+        error::or_panic(self.try_item(index))
+    }
 
-fn find_max<T: Copy + Zero + std::cmp::PartialOrd>(data: &[f64]) -> f64 {
-    data.iter()
-        .reduce(|x, y| if x > y { x } else { y })
-        .cloned()
-        .unwrap()
-}
+    /// Returns the element at the given multi-index, failing if the index has
+    /// the wrong number of axes or is out of bounds
+    pub fn try_item(&self, index: &[usize]) -> Result<T> {
+        Ok(self.data[layout::offset_of(0, &self.shape, &self.strides, index)?])
+    }
 
-impl Display for ArrayND {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "ArrayND, min: {:?}, max: {:?}, data[..100] {:?}",
-            self.min,
-            self.max,
-            &self.data[..100]
-        )
+    /// Iterates over the elements in row-major order, regardless of the memory layout
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        layout::Offsets::new(0, &self.shape, &self.strides).map(move |i| &self.data[i])
     }
-}
 
-impl Add<ArrayND> for ArrayND {
-    type Output = ArrayND;
-
-    fn add(self, rhs: Self) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.shape[0]);
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs.data).collect();
-        let data = data.iter().map(|(i, j)| i.clone() + j.clone()).collect();
-        let min = {
-            if lhs.min < rhs.min {
-                lhs.min
-            } else {
-                rhs.min
-            }
-        };
-        let max = {
-            if lhs.max < rhs.max {
-                lhs.max
-            } else {
-                rhs.max
-            }
-        };
+    /// Copies the elements into a Vec in row-major order
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().copied().collect()
+    }
 
-        ArrayND {
-            shape: vec![lhs.size],
-            size: lhs.size,
-            data,
-            min,
-            max,
-        }
+    /// Whether the data is stored contiguously in row-major order
+    pub fn is_standard_layout(&self) -> bool {
+        layout::is_row_major(&self.shape, &self.strides)
     }
-}
 
-impl Sub<ArrayND> for ArrayND {
-    type Output = ArrayND;
-
-    fn sub(self, rhs: Self) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.shape[0]);
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs.data).collect();
-        let data = data.iter().map(|(i, j)| i.clone() - j.clone()).collect();
-        let min = {
-            if lhs.min < rhs.min {
-                lhs.min
-            } else {
-                rhs.min
-            }
-        };
-        let max = {
-            if lhs.max < rhs.max {
-                lhs.max
-            } else {
-                rhs.max
-            }
-        };
+    /// Converts every element to `U` like an `as` cast, keeping the shape
+    ///
+    /// Use [`DynArray::astype`] to reject casts that lose information.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::new(vec![1.5, -2.5, 300.]);
+    /// assert_eq!(array.cast::<u8>().to_vec(), vec![1, 0, 255]);
+    /// ```
+    pub fn cast<U: Element>(&self) -> ArrayND<U> {
+        ArrayND::from_shape_vec(self.iter().map(|&x| x.cast()).collect(), self.shape.clone())
+    }
 
+    /// Rearranges the data into row-major order if it is not already
+    pub fn into_standard_layout(self) -> ArrayND<T> {
+        if self.is_standard_layout() {
+            return self;
+        }
+        let data = self.to_vec();
         ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
+            strides: layout::row_major_strides(&self.shape),
             data,
-            min,
-            max,
+            ..self
         }
     }
-}
 
-impl Mul<ArrayND> for ArrayND {
-    type Output = ArrayND;
-
-    fn mul(self, rhs: Self) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.shape[0]);
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs.data).collect();
-        let data = data.iter().map(|(i, j)| i.clone() * j.clone()).collect();
-        let min = {
-            if lhs.min < rhs.min {
-                lhs.min
-            } else {
-                rhs.min
-            }
-        };
-        let max = {
-            if lhs.max < rhs.max {
-                lhs.max
-            } else {
-                rhs.max
-            }
-        };
+    /// The elements in row-major order, borrowing the buffer when it already is
+    pub(crate) fn contiguous_data(&self) -> Cow<'_, [T]> {
+        if self.is_standard_layout() {
+            Cow::Borrowed(&self.data)
+        } else {
+            Cow::Owned(self.to_vec())
+        }
+    }
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
+    /// Gives the Array a new shape with the same number of elements
+    ///
+    /// One axis may be `-1`, in which case its length is inferred from the others.
+    /// The data is only copied when the Array is not in standard layout.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    /// let reshaped = array.reshape(&[-1, 2]);
+    /// assert_eq!(reshaped.shape(), &[3, 2]);
+    /// ```
+    pub fn reshape(self, shape: &[isize]) -> ArrayND<T> {
+        error::or_panic(self.try_reshape(shape))
+    }
+
+    /// Gives the Array a new shape, failing if it does not hold the same number of elements
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    /// assert!(array.try_reshape(&[4, -1]).is_err());
+    /// ```
+    pub fn try_reshape(self, shape: &[isize]) -> Result<ArrayND<T>> {
+        let shape = layout::infer_shape(self.size, shape)?;
+        let array = self.into_standard_layout();
+        Ok(ArrayND {
+            strides: layout::row_major_strides(&shape),
+            shape,
+            ..array
+        })
+    }
+
+    /// Reorders the axes so that axis `i` of the result is axis `axes[i]` of the Array
+    ///
+    /// Only the strides are permuted, the data is not moved.
+    /// Panics if `axes` is not a permutation of `0..ndim`.
+    pub fn permute_axes(self, axes: &[usize]) -> ArrayND<T> {
+        error::or_panic(self.try_permute_axes(axes))
+    }
+
+    /// Reorders the axes, failing if `axes` is not a permutation of `0..ndim`
+    pub fn try_permute_axes(self, axes: &[usize]) -> Result<ArrayND<T>> {
+        let (shape, strides) = layout::permute(&self.shape, &self.strides, axes)?;
+        Ok(ArrayND {
+            shape,
+            strides,
+            ..self
+        })
+    }
+
+    /// Cached (min, max), computing it if needed
+    fn try_stats(&self) -> Result<(T, T)> {
+        if let Some(stats) = self.stats.get() {
+            return Ok(*stats);
         }
+        let stats = self.min_max(false).map_or_else(empty_min_max, Ok)?;
+        Ok(*self.stats.get_or_init(|| stats))
     }
-}
 
-impl Div<ArrayND> for ArrayND {
-    type Output = ArrayND;
-
-    fn div(self, rhs: Self) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.shape[0]);
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs.data).collect();
-        let data = data.iter().map(|(i, j)| i.clone() / j.clone()).collect();
-        let min = {
-            if lhs.min < rhs.min {
-                lhs.min
+    /// (min, max) of the data, optionally ignoring NaN elements, or `None` if
+    /// there was nothing to compare
+    fn min_max(&self, skip_nan: bool) -> Option<(T, T)> {
+        parallel::run(self.size, |parallel| {
+            if parallel {
+                par_min_max(&self.data, skip_nan)
             } else {
-                rhs.min
+                seq_min_max(&self.data, skip_nan)
             }
-        };
-        let max = {
-            if lhs.max < rhs.max {
-                lhs.max
-            } else {
-                rhs.max
-            }
-        };
+        })
+    }
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
-        }
+    /// Discards the cached min and max, must be called whenever the data is mutated
+    fn invalidate_stats(&mut self) {
+        self.stats.take();
     }
 }
 
-impl Add<Vec<f64>> for ArrayND {
-    type Output = ArrayND;
-
-    fn add(self, rhs: Vec<f64>) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.len());
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs).collect();
-        let data: Vec<f64> = data.iter().map(|(i, j)| i.clone() + j.clone()).collect();
-        let rhs_min = find_min::<f64>(&data);
-        let rhs_max = find_min::<f64>(&data);
-        let min = {
-            if lhs.min < rhs_min {
-                lhs.min
-            } else {
-                rhs_min
-            }
-        };
-        let max = {
-            if lhs.max < rhs_max {
-                lhs.max
-            } else {
-                rhs_max
-            }
-        };
+impl<T: Element + Add<Output = T>> ArrayND<T> {
+    /// Sums the data inside the Array
+    ///
+    /// Uses pairwise summation, see [`sum_with`](ArrayND::sum_with) for
+    /// compensated summation.
+    ///
+    /// Uses a sequential sum when the Array size is small (at most 1 million
+    /// elements by default)
+    ///
+    /// Uses a parallel sum when the Array size is large, which gives exactly the
+    /// same result. The threshold and thread pool are set with [`Parallelism`].
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let data: Vec<f64> = vec![1.0, 2.0, 3.0];
+    /// let array: ArrayND = ArrayND::new(data);
+    /// assert_eq!(array.sum(), 6.0);
+    /// ```
+    pub fn sum(&self) -> T {
+        parallel::run(self.size, |parallel| {
+            summation::blocked_sum(&self.data, parallel, simd::sum)
+        })
+    }
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
+    /// Sequential pairwise sum
+    pub fn seq_sum(&self) -> T {
+        summation::blocked_sum(&self.data, false, simd::sum)
+    }
+
+    /// Parallel pairwise sum, bit-identical to [`seq_sum`](ArrayND::seq_sum)
+    /// on any number of threads
+    ///
+    /// Sequential on wasm.
+    pub fn par_sum(&self) -> T {
+        parallel::install(|| summation::blocked_sum(&self.data, true, simd::sum))
+    }
+
+    /// Sums the data inside the Array, treating NaN as zero
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::new(vec![1.0, f64::NAN, 2.0]);
+    /// assert_eq!(array.nansum(), 3.0);
+    /// ```
+    pub fn nansum(&self) -> T {
+        parallel::run(self.size, |parallel| {
+            summation::blocked_sum(&self.data, parallel, |block| {
+                simd::sum_map(block, |x| if x.is_nan() { T::zero() } else { x })
+            })
+        })
+    }
+
+    /// Sum of the elements as `f64`, with integers summed exactly in `i128`
+    /// so that small types cannot overflow
+    pub(crate) fn sum_f64(&self) -> f64 {
+        if T::DTYPE.is_float() {
+            return self.sum().to_f64();
         }
+        self.data.iter().map(|x| x.to_i128()).sum::<i128>() as f64
     }
-}
-impl Sub<Vec<f64>> for ArrayND {
-    type Output = ArrayND;
-
-    fn sub(self, rhs: Vec<f64>) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.len());
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs).collect();
-        let data: Vec<f64> = data.iter().map(|(i, j)| i.clone() - j.clone()).collect();
-        let rhs_min = find_min::<f64>(&data);
-        let rhs_max = find_min::<f64>(&data);
-        let min = {
-            if lhs.min < rhs_min {
-                lhs.min
-            } else {
-                rhs_min
-            }
-        };
-        let max = {
-            if lhs.max < rhs_max {
-                lhs.max
-            } else {
-                rhs_max
-            }
-        };
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
+    /// Sum of the elements treating NaN as zero as `f64`, with integers summed
+    /// exactly in `i128` like [`sum_f64`](ArrayND::sum_f64)
+    pub(crate) fn nansum_f64(&self) -> f64 {
+        if T::DTYPE.is_float() {
+            return self.nansum().to_f64();
         }
+        self.sum_f64()
     }
-}
-impl Mul<Vec<f64>> for ArrayND {
-    type Output = ArrayND;
-
-    fn mul(self, rhs: Vec<f64>) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.len());
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs).collect();
-        let data: Vec<f64> = data.iter().map(|(i, j)| i.clone() * j.clone()).collect();
-        let rhs_min = find_min::<f64>(&data);
-        let rhs_max = find_min::<f64>(&data);
-        let min = {
-            if lhs.min < rhs_min {
-                lhs.min
-            } else {
-                rhs_min
-            }
-        };
-        let max = {
-            if lhs.max < rhs_max {
-                lhs.max
-            } else {
-                rhs_max
-            }
-        };
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
+    /// Arithmetic mean of the Array, NaN if the Array is empty
+    ///
+    /// Float elements are summed in their own type before dividing, integers
+    /// in a wider type so the sum cannot overflow.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::new(vec![1.0, 2.0, 6.0]);
+    /// assert_eq!(array.mean(), 3.0);
+    /// ```
+    pub fn mean(&self) -> f64 {
+        self.sum_f64() / self.size as f64
+    }
+
+    /// Arithmetic mean of the elements that are not NaN
+    ///
+    /// NaN if the Array is empty or only contains NaN.
+    pub fn nanmean(&self) -> f64 {
+        let count = self.data.iter().filter(|val| !val.is_nan()).count();
+        self.nansum_f64() / count as f64
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, num: T) -> ArrayND<T> {
+        self.data.iter_mut().for_each(|x| *x = x.wrapping_add(num));
+        self.invalidate_stats();
+        self
+    }
+
+    pub fn arange(start: T, stop: T, step: T) -> ArrayND<T> {
+        let mut data: Vec<T> = Vec::new();
+        let mut i = start;
+        while i < stop {
+            data.push(i);
+            i = i + step;
         }
+        ArrayND::new(data)
     }
 }
-impl Div<Vec<f64>> for ArrayND {
-    type Output = ArrayND;
-
-    fn div(self, rhs: Vec<f64>) -> ArrayND {
-        let lhs = self;
-        assert_eq!(lhs.shape[0], rhs.len());
-        let data: Vec<(f64, f64)> = lhs.data.into_iter().zip(rhs).collect();
-        let data: Vec<f64> = data.iter().map(|(i, j)| i.clone() / j.clone()).collect();
-        let rhs_min = find_min::<f64>(&data);
-        let rhs_max = find_min::<f64>(&data);
-        let min = {
-            if lhs.min < rhs_min {
-                lhs.min
-            } else {
-                rhs_min
-            }
-        };
-        let max = {
-            if lhs.max < rhs_max {
-                lhs.max
-            } else {
-                rhs_max
-            }
-        };
 
-        ArrayND {
-            shape: vec![lhs.shape[0]],
-            size: lhs.size,
-            data,
-            min,
-            max,
-        }
+impl<T: Element> ArrayND<T>
+where
+    Standard: Distribution<T>,
+{
+    /// Generates a random 1D Array
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::random(10);
+    /// ```
+    pub fn random(size: usize) -> ArrayND<T> {
+        ArrayND::new(random_vec(size, |rng| rng.gen::<T>()))
+    }
+}
+
+impl<T: Element + SampleUniform> ArrayND<T> {
+    /// Generates a random 1D Array with a range
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::random_range(10, 1., 10.);
+    /// let integers: ArrayND<i64> = ArrayND::random_range(10, 1, 10);
+    /// ```
+    pub fn random_range(size: usize, min: T, max: T) -> ArrayND<T> {
+        ArrayND::new(random_vec(size, |rng| rng.gen_range(min..max)))
     }
 }
 
-impl Add<f64> for ArrayND {
-    type Output = ArrayND;
+pub fn asarray<T: Element>(data: Vec<T>) -> ArrayND<T> {
+    ArrayND::new(data)
+}
 
-    fn add(mut self, rhs: f64) -> ArrayND {
-        for el in self.data.iter_mut() {
-            *el += rhs
-        }
-        self.min += rhs;
-        self.max += rhs;
-        self
+/// `size` values drawn with `sample`, filled by multiple threads for large sizes
+fn random_vec<T: Element>(
+    size: usize,
+    sample: impl Fn(&mut ThreadRng) -> T + Sync + Send,
+) -> Vec<T> {
+    parallel::run(size, |parallel| {
+        let mut data = vec![T::zero(); size];
+        fill_random(&mut data, parallel, sample);
+        data
+    })
+}
+
+#[cfg(target_family = "wasm")]
+fn fill_random<T: Element>(
+    data: &mut [T],
+    _parallel: bool,
+    sample: impl Fn(&mut ThreadRng) -> T + Sync + Send,
+) {
+    let mut rng = rand::thread_rng();
+    data.iter_mut().for_each(|x| *x = sample(&mut rng));
+}
+
+/// Fills `data` with values drawn with `sample`, every thread drawing from its own generator
+#[cfg(target_family = "unix")]
+fn fill_random<T: Element>(
+    data: &mut [T],
+    parallel: bool,
+    sample: impl Fn(&mut ThreadRng) -> T + Sync + Send,
+) {
+    if parallel {
+        data.par_chunks_mut(summation::BLOCK)
+            .for_each_init(rand::thread_rng, |rng, chunk| {
+                chunk.iter_mut().for_each(|x| *x = sample(rng))
+            });
+    } else {
+        let mut rng = rand::thread_rng();
+        data.iter_mut().for_each(|x| *x = sample(&mut rng));
     }
 }
 
-impl Sub<f64> for ArrayND {
-    type Output = ArrayND;
+/// (min, max) of a reduction over no elements, NaN for float types
+///
+/// Fails for other types since they have no value to represent it.
+fn empty_min_max<T: Element>() -> Result<(T, T)> {
+    let nan = T::nan().ok_or_else(|| {
+        NumrsError::EmptyReduction("zero-size array has no minimum or maximum".to_string())
+    })?;
+    Ok((nan, nan))
+}
 
-    fn sub(mut self, rhs: f64) -> ArrayND {
-        for el in self.data.iter_mut() {
-            *el -= rhs
-        }
-        self.min -= rhs;
-        self.max -= rhs;
-        self
+/// Combines two partial (min, max) results, letting NaN win
+fn merge_min_max<T: Element>(lhs: Option<(T, T)>, rhs: Option<(T, T)>) -> Option<(T, T)> {
+    match (lhs, rhs) {
+        (None, other) | (other, None) => other,
+        (Some(lhs), _) if lhs.0.is_nan() => Some(lhs),
+        (_, Some(rhs)) if rhs.0.is_nan() => Some(rhs),
+        (Some(lhs), Some(rhs)) => Some((
+            if rhs.0 < lhs.0 { rhs.0 } else { lhs.0 },
+            if rhs.1 > lhs.1 { rhs.1 } else { lhs.1 },
+        )),
     }
 }
-impl Mul<f64> for ArrayND {
-    type Output = ArrayND;
 
-    fn mul(mut self, rhs: f64) -> ArrayND {
-        for el in self.data.iter_mut() {
-            *el *= rhs
-        }
-        self.min *= rhs;
-        self.max *= rhs;
-        self
+/// Folds one element into a partial (min, max), ignoring it if `skip_nan` is set and it is NaN
+fn fold_min_max<T: Element>(acc: Option<(T, T)>, x: T, skip_nan: bool) -> Option<(T, T)> {
+    if skip_nan && x.is_nan() {
+        acc
+    } else {
+        merge_min_max(acc, Some((x, x)))
     }
 }
 
-impl Div<f64> for ArrayND {
-    type Output = ArrayND;
+/// (min, max) of the data, or `None` if there are no elements
+///
+/// A NaN element makes the result NaN unless `skip_nan` is set, in which case
+/// NaN elements are ignored.
+fn seq_min_max<T: Element>(data: &[T], skip_nan: bool) -> Option<(T, T)> {
+    simd::min_max(data).or_else(|| {
+        data.iter()
+            .fold(None, |acc, &x| fold_min_max(acc, x, skip_nan))
+    })
+}
 
-    fn div(mut self, rhs: f64) -> ArrayND {
-        for el in self.data.iter_mut() {
-            *el /= rhs
-        }
-        self.min /= rhs;
-        self.max /= rhs;
-        self
+#[cfg(target_family = "wasm")]
+fn par_min_max<T: Element>(data: &[T], skip_nan: bool) -> Option<(T, T)> {
+    seq_min_max(data, skip_nan)
+}
+
+#[cfg(target_family = "unix")]
+fn par_min_max<T: Element>(data: &[T], skip_nan: bool) -> Option<(T, T)> {
+    data.par_chunks(summation::REDUCE_BLOCK)
+        .map(|block| seq_min_max(block, skip_nan))
+        .reduce(|| None, merge_min_max)
+}
+
+impl<T: Element> Display for ArrayND<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ArrayND {:?}, min: {:?}, max: {:?}, data[..100] {:?}",
+            self.shape,
+            self.min(),
+            self.max(),
+            self.iter().take(100).collect::<Vec<&T>>()
+        )
     }
 }
 
-impl Debug for ArrayND {
+impl<T: Element> Debug for ArrayND<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.shape[0] > 100 {
-            let print_limit = min(self.shape[0], 100);
+        if self.size > 100 {
+            let print_limit = min(self.size, 100);
             write!(
                 f,
                 "ArrayND {:?}, min: {:?}, max: {:?}, data[..{:?}] {:?}...",
                 self.shape,
-                self.min,
-                self.max,
+                self.min(),
+                self.max(),
                 print_limit,
-                &self.data[..print_limit]
+                self.iter().take(print_limit).collect::<Vec<&T>>()
             )
         } else {
             write!(
                 f,
                 "ArrayND {:?}, min: {:?}, max: {:?}, data: {:?}",
                 self.shape,
-                self.min,
-                self.max,
-                &self.data[..self.shape[0]]
+                self.min(),
+                self.max(),
+                self.iter().collect::<Vec<&T>>()
             )
         }
     }
 }
 
-impl PartialEq<ArrayND> for ArrayND {
-    fn eq(&self, other: &ArrayND) -> bool {
-        self.data == other.data
-    }
-
-    fn ne(&self, other: &ArrayND) -> bool {
-        self.data == other.data
+impl<T: Element> PartialEq<ArrayND<T>> for ArrayND<T> {
+    fn eq(&self, other: &ArrayND<T>) -> bool {
+        self.shape == other.shape && self.iter().eq(other.iter())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ArrayND;
+    use crate::{ArrayND, NumrsError};
 
-    // fn get_array_1d_integer() -> ArrayND {
-    //     ArrayND::new(vec![1, 2, 3, 4, 5, 6, 7])
-    // }
+    fn get_array_1d_integer() -> ArrayND<i32> {
+        ArrayND::new(vec![1, 2, 3, 4, 5, 6, 7])
+    }
 
     fn get_array_1d_float() -> ArrayND {
         ArrayND::new(vec![1., 2., 3., 4., 5., 6., 7.])
     }
 
     #[test]
-    // fn add_integer() {
-    //     let data_addition_mult = get_array_1d_integer()
-    //         + get_array_1d_integer()
-    //         + get_array_1d_integer()
-    //         + get_array_1d_integer()
-    //         + get_array_1d_integer();
-    //     let array1 = get_array_1d_integer();
-    //     let array2 = get_array_1d_integer();
-    //     let data_addition = array1.clone() + array2;
+    fn add_integer() {
+        let data_addition_mult = get_array_1d_integer()
+            + get_array_1d_integer()
+            + get_array_1d_integer()
+            + get_array_1d_integer()
+            + get_array_1d_integer();
+        let array1 = get_array_1d_integer();
+        let array2 = get_array_1d_integer();
+        let data_addition = array1.clone() + array2;
 
-    //     assert_eq!(data_addition, ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14]));
-    //     assert_eq!(
-    //         array1.clone() + vec![1, 2, 3, 4, 5, 6, 7],
-    //         ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14])
-    //     );
-    //     assert_eq!(array1 + 1, ArrayND::new(vec![2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(data_addition, ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14]));
+        assert_eq!(
+            array1.clone() + vec![1, 2, 3, 4, 5, 6, 7],
+            ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14])
+        );
+        assert_eq!(array1 + 1, ArrayND::new(vec![2, 3, 4, 5, 6, 7, 8]));
 
-    //     let expected_array = ArrayND::new(vec![5, 10, 15, 20, 25, 30, 35]);
-    //     assert_eq!(data_addition_mult, expected_array);
+        let expected_array = ArrayND::new(vec![5, 10, 15, 20, 25, 30, 35]);
+        assert_eq!(data_addition_mult, expected_array);
 
-    //     let incorrect_array: ArrayND<i32> = ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14, 16]);
-    //     assert_ne!(data_addition, incorrect_array);
+        let incorrect_array: ArrayND<i32> = ArrayND::new(vec![2, 4, 6, 8, 10, 12, 14, 16]);
+        assert_ne!(data_addition, incorrect_array);
+
+        let incorrect_array = ArrayND::new(vec![2, 4, 6, 8, 10, 12, 123]);
+        assert_ne!(data_addition, incorrect_array)
+    }
 
-    //     let incorrect_array = ArrayND::new(vec![2, 4, 6, 8, 10, 12, 123]);
-    //     assert_ne!(data_addition, incorrect_array)
-    // }
     #[test]
     fn add_float() {
         let data_addition_mult = get_array_1d_float()
@@ -673,6 +846,36 @@ mod tests {
         assert_eq!(array1.par_sum(), 28.);
     }
 
+    #[test]
+    fn mean_small_integers() {
+        let array = ArrayND::new(vec![100i8, 100, 127]);
+        assert_eq!(array.mean(), 109.);
+        assert_eq!(array.nanmean(), 109.);
+        assert_eq!(ArrayND::new(vec![255u8; 4]).mean(), 255.);
+    }
+
+    #[test]
+    fn small_integers_wrap() {
+        assert_eq!(ArrayND::new(vec![200u8, 100]).sum(), 44);
+        assert_eq!(ArrayND::new(vec![200u8; 100]).sum(), 32);
+        assert_eq!(
+            ArrayND::new(vec![100i8]) + ArrayND::new(vec![100i8]),
+            ArrayND::new(vec![-56i8])
+        );
+        assert_eq!(
+            ArrayND::new(vec![0u8, 1]) - 2,
+            ArrayND::new(vec![254u8, 255])
+        );
+        assert_eq!(16u8 * ArrayND::new(vec![16u8]), ArrayND::new(vec![0u8]));
+        let mut array = ArrayND::new(vec![i8::MIN]);
+        array -= 1;
+        assert_eq!(array.to_vec(), vec![i8::MAX]);
+        assert_eq!(
+            ArrayND::new(vec![100i8, 100]).dot(&ArrayND::new(vec![2i8, 1])),
+            44
+        );
+    }
+
     #[test]
     fn mixed_operation_float() {
         let array1 = get_array_1d_float();
@@ -685,17 +888,444 @@ mod tests {
     #[test]
     fn random() {
         let array1: ArrayND = ArrayND::random(3);
-        // let array2: ArrayND<i64> = ArrayND::random_range(3, 1., 10.);
+        let array2: ArrayND<i64> = ArrayND::random_range(3, 1, 10);
 
         assert_eq!(array1.shape, vec![3]);
         assert!(array1.data[0] >= 0. && array1.data[0] < 1.);
-        // assert!(array2.data[0] >= 1 && array2.data[0] <= 10);
+        assert!(array2.data[0] >= 1 && array2.data[0] < 10);
+    }
+
+    #[test]
+    fn shape_nd() {
+        let array = ArrayND::from_shape_vec((0..24).map(|x| x as f64).collect(), vec![2, 3, 4]);
+
+        assert_eq!(array.ndim(), 3);
+        assert_eq!(array.shape(), &[2, 3, 4]);
+        assert_eq!(array.strides(), &[12, 4, 1]);
+        assert_eq!(array.item(&[0, 0, 0]), 0.);
+        assert_eq!(array.item(&[1, 2, 3]), 23.);
+        assert_eq!(array.item(&[1, 0, 2]), 14.);
+        assert_eq!(get_array_1d_float().ndim(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn shape_nd_wrong_size() {
+        ArrayND::from_shape_vec(vec![1., 2., 3.], vec![2, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn item_out_of_bounds() {
+        let array = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+        array.item(&[2, 0]);
+    }
+
+    #[test]
+    fn add_float_nd() {
+        let array1 = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![2, 3]);
+        let array2 = ArrayND::from_shape_vec(vec![1., 1., 1., 2., 2., 2.], vec![2, 3]);
+
+        let result = array1 + array2;
+        assert_eq!(result.shape(), &[2, 3]);
+        assert_eq!(
+            result,
+            ArrayND::from_shape_vec(vec![2., 3., 4., 6., 7., 8.], vec![2, 3])
+        );
+        assert_ne!(result, ArrayND::new(vec![2., 3., 4., 6., 7., 8.]));
+    }
+
+    #[test]
+    #[should_panic(expected = "could not be broadcast together")]
+    fn add_float_shape_mismatch() {
+        let array1 = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![2, 3]);
+        let array2 = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![3, 2]);
+        let _ = array1 + array2;
+    }
+
+    #[test]
+    fn broadcast_float() {
+        let column = ArrayND::from_shape_vec(vec![0., 10., 20.], vec![3, 1]);
+        let row = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![1, 4]);
+
+        let result = column.clone() + row.clone();
+        assert_eq!(result.shape(), &[3, 4]);
+        assert_eq!(
+            result.to_vec(),
+            vec![1., 2., 3., 4., 11., 12., 13., 14., 21., 22., 23., 24.]
+        );
+        assert_eq!((row.clone() - column.clone()).item(&[2, 0]), -19.);
+        assert_eq!((column.clone() * row.clone()).item(&[1, 3]), 40.);
+        assert_eq!((row / column).item(&[1, 1]), 0.2);
+    }
+
+    #[test]
+    fn broadcast_float_in_place() {
+        let matrix = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+
+        assert_eq!(
+            matrix.clone() + vec![1., 2., 3.],
+            ArrayND::from_shape_vec(vec![1., 3., 5., 4., 6., 8.], vec![2, 3])
+        );
+        assert_eq!(
+            matrix.clone().transpose() * ArrayND::new(vec![1., -1.]),
+            ArrayND::from_shape_vec(vec![0., -3., 1., -4., 2., -5.], vec![3, 2])
+        );
+        assert_eq!(
+            ArrayND::new(vec![1., 2., 3.]) - matrix,
+            ArrayND::from_shape_vec(vec![1., 1., 1., -2., -2., -2.], vec![2, 3])
+        );
     }
 
-    // #[test]
-    // fn arange_test() {
-    //     let array1 = arange(10);
-    //     let correct_array: ArrayND<i64> = ArrayND::new(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
-    //     assert_eq!(array1, correct_array);
-    // }
+    #[test]
+    #[should_panic(expected = "could not be broadcast together")]
+    fn broadcast_vec_mismatch() {
+        let _ = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]) + vec![1., 2.];
+    }
+
+    #[test]
+    fn reshape() {
+        let array = ArrayND::arange(0., 12., 1.);
+
+        let reshaped = array.clone().reshape(&[3, 4]);
+        assert_eq!(reshaped.shape(), &[3, 4]);
+        assert_eq!(reshaped.item(&[2, 1]), 9.);
+        assert_eq!(array.clone().reshape(&[2, -1, 3]).shape(), &[2, 2, 3]);
+        assert_eq!(reshaped.ravel(), array);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reshape_wrong_size() {
+        ArrayND::arange(0., 12., 1.).reshape(&[5, -1]);
+    }
+
+    #[test]
+    fn transpose() {
+        let array = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+        let transposed = array.clone().transpose();
+
+        assert_eq!(transposed.shape(), &[3, 2]);
+        assert_eq!(transposed.strides(), &[1, 3]);
+        assert!(!transposed.is_standard_layout());
+        assert_eq!(transposed.to_vec(), vec![0., 3., 1., 4., 2., 5.]);
+        assert_eq!(transposed.item(&[1, 0]), array.item(&[0, 1]));
+        assert_eq!(transposed.clone().transpose(), array);
+
+        let reshaped = transposed.clone().reshape(&[6]);
+        assert!(reshaped.is_standard_layout());
+        assert_eq!(reshaped.to_vec(), vec![0., 3., 1., 4., 2., 5.]);
+        assert_eq!(transposed.flatten(), reshaped);
+    }
+
+    #[test]
+    fn permute_axes() {
+        let array = ArrayND::arange(0., 24., 1.).reshape(&[2, 3, 4]);
+        let permuted = array.clone().permute_axes(&[1, 2, 0]);
+
+        assert_eq!(permuted.shape(), &[3, 4, 2]);
+        assert_eq!(permuted.item(&[2, 1, 1]), array.item(&[1, 2, 1]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn permute_axes_repeated() {
+        ArrayND::arange(0., 24., 1.)
+            .reshape(&[2, 3, 4])
+            .permute_axes(&[0, 0, 1]);
+    }
+
+    #[test]
+    fn add_float_transposed() {
+        let array = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+        let other = ArrayND::arange(0., 6., 1.).reshape(&[3, 2]).transpose();
+
+        let result = array.clone() + other.clone();
+        assert_eq!(
+            result,
+            ArrayND::from_shape_vec(vec![0., 3., 6., 4., 7., 10.], vec![2, 3])
+        );
+        assert_eq!(other.clone() + other.clone(), other * 2.);
+    }
+
+    #[test]
+    fn stats_lazy() {
+        let array = get_array_1d_float();
+        assert!(array.stats.get().is_none());
+
+        assert_eq!(array.min(), 1.);
+        assert_eq!(array.max(), 7.);
+        assert!(array.stats.get().is_some());
+    }
+
+    #[test]
+    fn stats_after_operations() {
+        let array = get_array_1d_float() * -2.;
+        assert_eq!((array.min(), array.max()), (-14., -2.));
+
+        let array = get_array_1d_float() / -1.;
+        assert_eq!((array.min(), array.max()), (-7., -1.));
+
+        let array = 10. - get_array_1d_float();
+        assert_eq!((array.min(), array.max()), (3., 9.));
+
+        let array = get_array_1d_float() + ArrayND::new(vec![10., 0., 0., 0., 0., 0., -10.]);
+        assert_eq!((array.min(), array.max()), (-3., 11.));
+
+        let array = get_array_1d_float() - vec![0., 0., 0., 0., 0., 0., 20.];
+        assert_eq!((array.min(), array.max()), (-13., 6.));
+
+        let array = get_array_1d_float().add(-3.);
+        assert_eq!((array.min(), array.max()), (-2., 4.));
+    }
+
+    #[test]
+    fn stats_invalidated_on_mutation() {
+        let mut array = get_array_1d_float();
+        assert_eq!(array.max(), 7.);
+
+        array *= -1.;
+        assert_eq!((array.min(), array.max()), (-7., -1.));
+
+        array.view_mut().slice_axis(0, 0..1).fill(100.);
+        assert_eq!((array.min(), array.max()), (-7., 100.));
+    }
+
+    #[test]
+    fn stats_nan() {
+        let array = ArrayND::new(vec![1., f64::NAN, -3.]);
+        assert!(array.min().is_nan());
+        assert!(array.max().is_nan());
+
+        let array = ArrayND::new(vec![f64::NAN, 1., -3.]);
+        assert!(array.min().is_nan());
+        assert!(array.max().is_nan());
+
+        let array = ArrayND::new(vec![0f64, 1., 2.]) / 0.;
+        assert!(array.min().is_nan());
+    }
+
+    #[test]
+    fn stats_empty() {
+        let array: ArrayND = ArrayND::new(vec![]);
+        assert!(array.min().is_nan());
+        assert!(array.max().is_nan());
+    }
+
+    #[test]
+    fn nan_reductions() {
+        let array = ArrayND::new(vec![f64::NAN, 4., -2., f64::NAN, 1.]);
+
+        assert!(array.sum().is_nan());
+        assert!(array.mean().is_nan());
+        assert_eq!(array.nanmin(), -2.);
+        assert_eq!(array.nanmax(), 4.);
+        assert_eq!(array.nansum(), 3.);
+        assert_eq!(array.nanmean(), 1.);
+
+        let all_nan = ArrayND::new(vec![f64::NAN, f64::NAN]);
+        assert!(all_nan.nanmin().is_nan());
+        assert!(all_nan.nanmax().is_nan());
+        assert_eq!(all_nan.nansum(), 0.);
+        assert!(all_nan.nanmean().is_nan());
+
+        let infinite = ArrayND::new(vec![f64::INFINITY, f64::NAN]);
+        assert_eq!(infinite.nanmin(), f64::INFINITY);
+        assert_eq!(infinite.nanmax(), f64::INFINITY);
+    }
+
+    #[test]
+    fn nan_reductions_parallel() {
+        let mut data = vec![1.; 1_000_001];
+        data[10] = f64::NAN;
+        data[20] = -5.;
+        let array = ArrayND::new(data);
+
+        assert!(array.max().is_nan());
+        assert_eq!(array.nanmin(), -5.);
+        assert_eq!(array.nanmax(), 1.);
+    }
+
+    #[test]
+    fn empty() {
+        let array: ArrayND = ArrayND::new(vec![]);
+
+        assert_eq!(array.shape(), &[0]);
+        assert_eq!(array.sum(), 0.);
+        assert_eq!(array.nansum(), 0.);
+        assert!(array.mean().is_nan());
+        assert!(array.nanmin().is_nan());
+        assert_eq!(array.to_string(), "");
+        assert_eq!(
+            format!("{:?}", array),
+            "ArrayND [0], min: NaN, max: NaN, data: []"
+        );
+        assert_eq!(array.clone() + 1., array);
+        assert_eq!(ArrayND::arange(0., 0., 1.), array);
+        assert_eq!(ArrayND::random(0), array);
+    }
+
+    #[test]
+    fn empty_nd() {
+        let array = ArrayND::from_shape_vec(vec![], vec![2, 0, 3]);
+
+        assert_eq!(array.shape(), &[2, 0, 3]);
+        assert_eq!(array.ndim(), 3);
+        assert_eq!(array.to_vec(), Vec::<f64>::new());
+        assert_eq!(array.clone().transpose().shape(), &[3, 0, 2]);
+        assert_eq!(array.clone().reshape(&[0, 5]).shape(), &[0, 5]);
+        assert_eq!(array.clone().ravel().shape(), &[0]);
+        assert_eq!(array.view().slice_axis(2, 1..2).shape(), &[2, 0, 1]);
+
+        let row = ArrayND::new(vec![1., 2., 3.]);
+        let result = &array + &row;
+        assert_eq!(result.shape(), &[2, 0, 3]);
+        assert_eq!(
+            (&array * &ArrayND::from_shape_vec(vec![], vec![0, 1])).shape(),
+            &[2, 0, 3]
+        );
+
+        let mut array = array;
+        array += &row;
+        array /= 0.;
+        assert_eq!(array.shape(), &[2, 0, 3]);
+        assert_eq!(array.sum(), 0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "could not be broadcast together")]
+    fn empty_broadcast_mismatch() {
+        let _ = ArrayND::new(vec![]) + ArrayND::new(vec![1., 2.]);
+    }
+
+    #[test]
+    fn stats_parallel() {
+        let mut data = vec![0.5; 1_000_001];
+        data[123_456] = -4.;
+        data[999_999] = 8.;
+        let array = ArrayND::new(data);
+
+        assert_eq!((array.min(), array.max()), (-4., 8.));
+    }
+
+    #[test]
+    fn arange_test() {
+        let array1 = ArrayND::arange(1, 10, 1);
+        let correct_array: ArrayND<i64> = ArrayND::new(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(array1, correct_array);
+    }
+
+    #[test]
+    fn other_element_types() {
+        let floats: ArrayND<f32> = ArrayND::new(vec![1.5, -2., f32::NAN]);
+        assert_eq!(floats.nanmin(), -2.);
+        assert!(floats.max().is_nan());
+
+        let bytes: ArrayND<u8> = ArrayND::from_shape_vec(vec![1, 2, 3, 4], vec![2, 2]);
+        assert_eq!((2 * bytes.transpose()).to_vec(), vec![2, 6, 4, 8]);
+
+        let mask = ArrayND::new(vec![true, false, true]);
+        assert_eq!((mask.min(), mask.max()), (false, true));
+    }
+
+    #[test]
+    #[should_panic(expected = "zero-size array has no minimum or maximum")]
+    fn empty_integer_min() {
+        let array: ArrayND<i32> = ArrayND::new(vec![]);
+        array.min();
+    }
+
+    #[test]
+    fn try_errors() {
+        let array = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+
+        assert!(matches!(
+            ArrayND::try_from_shape_vec(vec![1., 2., 3.], vec![2, 2]),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            array.try_item(&[2, 0]),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(matches!(
+            array.clone().try_permute_axes(&[1, 1]),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+
+        let error = array.clone().try_reshape(&[4, -1]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot reshape array of size 6 into shape [4, -1]"
+        );
+
+        let empty: ArrayND<u8> = ArrayND::new(vec![]);
+        assert!(matches!(
+            empty.try_min(),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+        assert!(matches!(
+            empty.try_nanmax(),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+    }
+
+    #[test]
+    fn try_operators() {
+        let mut array = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+        let row = ArrayND::new(vec![1., 1., 1.]);
+        let column = ArrayND::new(vec![1., 1.]);
+
+        assert_eq!(array.try_add(&row).unwrap(), &array + &row);
+        assert!(matches!(
+            array.try_mul(&column),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+
+        array.try_sub_assign(&row).unwrap();
+        assert_eq!(array.to_vec(), vec![-1., 0., 1., 2., 3., 4.]);
+
+        let mut vector = row.clone();
+        let error = vector.try_add_assign(&array).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "could not broadcast shape [2, 3] into shape [3]"
+        );
+        assert_eq!(vector, row);
+    }
+
+    #[test]
+    fn try_div_integers() {
+        let mut array = ArrayND::from_shape_vec(vec![4, -6, 8, 10], vec![2, 2]);
+        let divisors = ArrayND::new(vec![2, 0]);
+        assert!(matches!(
+            array.try_div(&divisors),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        assert!(matches!(
+            array.try_div_assign(&divisors),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        assert_eq!(array.to_vec(), vec![4, -6, 8, 10]);
+        assert!(ArrayND::new(vec![i32::MIN])
+            .try_div(&ArrayND::new(vec![-1]))
+            .is_err());
+
+        let divisors = ArrayND::new(vec![2, -2]);
+        assert_eq!(
+            array.try_div(&divisors).unwrap().to_vec(),
+            vec![2, 3, 4, -5]
+        );
+        let mut transposed = array.transpose();
+        let strides = transposed.strides().to_vec();
+        transposed.try_div_assign(&divisors).unwrap();
+        assert_eq!(transposed.to_vec(), vec![2, -4, -3, -5]);
+        assert_eq!(transposed.strides(), strides);
+        assert_eq!(
+            ArrayND::new(vec![1.])
+                .try_div(&ArrayND::new(vec![0.]))
+                .unwrap()
+                .to_vec(),
+            vec![f64::INFINITY]
+        );
+    }
 }
@@ -1,12 +1,8 @@
-mod lib;
-
 use std::time::Instant;
 
-use lib::ArrayND;
-
+use numrs::ArrayND;
 
 fn main() {
-
     let array: ArrayND = ArrayND::random(100_000_000);
     // let array: ArrayND<_> = numrs::arange(100_000_000);
     println!("{:?}", array);
@@ -19,5 +15,10 @@ fn main() {
     let now = Instant::now();
     println!("sum combo: {:?}", array.sum());
     println!("Time Taken: {:?}", now.elapsed());
+    let now = Instant::now();
+    let matrix = array.reshape(&[10_000, 10_000]);
+    let block = matrix.view().transpose().slice_axis(0, 0..1_000);
+    println!("sum view: {:?}", block.sum());
+    println!("Time Taken: {:?}", now.elapsed());
     println!("Hello, world!");
 }
@@ -0,0 +1,221 @@
+//! Element-wise comparisons and boolean masks
+//!
+//! Comparisons return an `ArrayND<bool>` with the broadcast shape of the
+//! operands. Masks are combined with the `&`, `|`, `^` and `!` operators and
+//! used to pick out or overwrite elements with [`ArrayND::select`] and
+//! [`ArrayND::mask_assign`].
+//!
+//! As with `PartialOrd`, every comparison involving NaN is `false`, except
+//! `ne_elem` which is `true`.
+
+use crate::{broadcast, error, layout, ops, ArrayND, Element, NumrsError, Result};
+
+macro_rules! impl_comparison {
+    ($method:ident, $try_method:ident, $scalar_method:ident, $op:tt, $name:literal) => {
+        impl<T: Element> ArrayND<T> {
+            #[doc = concat!("Element-wise `", $name, "` with broadcasting, returning a bool Array")]
+            ///
+            /// Panics if the shapes cannot be broadcast together.
+            pub fn $method(&self, rhs: &ArrayND<T>) -> ArrayND<bool> {
+                error::or_panic(self.$try_method(rhs))
+            }
+
+            #[doc = concat!("Element-wise `", $name, "` with broadcasting, failing if the shapes are incompatible")]
+            pub fn $try_method(&self, rhs: &ArrayND<T>) -> Result<ArrayND<bool>> {
+                ops::zip_new(&self.operand(), &rhs.operand(), None, |x, y| x $op y)
+            }
+
+            #[doc = concat!("Compares every element with `", $name, "` against `value`")]
+            pub fn $scalar_method(&self, value: T) -> ArrayND<bool> {
+                ArrayND::from_shape_vec(self.iter().map(|&x| x $op value).collect(), self.shape.clone())
+            }
+        }
+    };
+}
+
+impl_comparison!(gt, try_gt, gt_scalar, >, ">");
+impl_comparison!(ge, try_ge, ge_scalar, >=, ">=");
+impl_comparison!(lt, try_lt, lt_scalar, <, "<");
+impl_comparison!(le, try_le, le_scalar, <=, "<=");
+impl_comparison!(eq_elem, try_eq_elem, eq_elem_scalar, ==, "==");
+impl_comparison!(ne_elem, try_ne_elem, ne_elem_scalar, !=, "!=");
+
+impl<T: Element> ArrayND<T> {
+    /// Copies the elements where `mask` is `true` into a 1D Array, in row-major order
+    ///
+    /// Panics if `mask` does not have the same shape as the Array.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1., 5., 2., 8.], vec![2, 2]);
+    /// let mask = array.gt_scalar(3.) | array.eq_elem_scalar(1.);
+    /// assert_eq!(array.select(&mask).to_vec(), vec![1., 5., 8.]);
+    /// ```
+    pub fn select(&self, mask: &ArrayND<bool>) -> ArrayND<T> {
+        error::or_panic(self.try_select(mask))
+    }
+
+    /// Copies the elements where `mask` is `true` into a 1D Array, failing if
+    /// `mask` does not have the same shape as the Array
+    pub fn try_select(&self, mask: &ArrayND<bool>) -> Result<ArrayND<T>> {
+        if mask.shape != self.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "mask of shape {:?} does not match array of shape {:?}",
+                mask.shape, self.shape
+            )));
+        }
+        let data: Vec<T> = self
+            .iter()
+            .zip(mask.iter())
+            .filter(|(_, &keep)| keep)
+            .map(|(&x, _)| x)
+            .collect();
+        Ok(ArrayND::new(data))
+    }
+
+    /// Sets the elements where `mask` is `true` to `value`
+    ///
+    /// The mask is broadcast to the shape of the Array, so a mask over the last
+    /// axis selects whole columns. Panics if it cannot be broadcast.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let mut array: ArrayND = ArrayND::new(vec![1., -2., 3., -4.]);
+    /// array.mask_assign(&array.lt_scalar(0.), 0.);
+    /// assert_eq!(array.to_vec(), vec![1., 0., 3., 0.]);
+    /// ```
+    pub fn mask_assign(&mut self, mask: &ArrayND<bool>, value: T) {
+        error::or_panic(self.try_mask_assign(mask, value))
+    }
+
+    /// Sets the elements where `mask` is `true` to `value`, failing without
+    /// modifying the Array if `mask` cannot be broadcast to its shape
+    pub fn try_mask_assign(&mut self, mask: &ArrayND<bool>, value: T) -> Result<()> {
+        let shape = ops::result_shape(&self.shape, &mask.shape)?;
+        if shape != self.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "could not broadcast mask of shape {:?} into shape {:?}",
+                mask.shape, self.shape
+            )));
+        }
+        let mask_strides = broadcast::broadcast_strides(&mask.shape, &mask.strides, &shape);
+        let offsets = layout::Offsets::new(0, &self.shape, &self.strides);
+        let mask_offsets = layout::Offsets::new(0, &shape, &mask_strides);
+        for (i, j) in offsets.zip(mask_offsets) {
+            if mask.data[j] {
+                self.data[i] = value;
+            }
+        }
+        self.invalidate_stats();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayND, NumrsError};
+
+    fn get_matrix() -> ArrayND<i32> {
+        ArrayND::from_shape_vec(vec![1, 5, 3, 4, 2, 6], vec![2, 3])
+    }
+
+    #[test]
+    fn compare_arrays() {
+        let a = ArrayND::new(vec![1, 2, 3]);
+        let b = ArrayND::new(vec![3, 2, 1]);
+
+        assert_eq!(a.gt(&b).to_vec(), vec![false, false, true]);
+        assert_eq!(a.ge(&b).to_vec(), vec![false, true, true]);
+        assert_eq!(a.lt(&b).to_vec(), vec![true, false, false]);
+        assert_eq!(a.le(&b).to_vec(), vec![true, true, false]);
+        assert_eq!(a.eq_elem(&b).to_vec(), vec![false, true, false]);
+        assert_eq!(a.ne_elem(&b).to_vec(), vec![true, false, true]);
+    }
+
+    #[test]
+    fn compare_broadcast() {
+        let row = ArrayND::new(vec![2, 5, 3]);
+        let mask = get_matrix().ge(&row);
+
+        assert_eq!(mask.shape(), &[2, 3]);
+        assert_eq!(mask.to_vec(), vec![false, true, true, true, false, true]);
+        assert!(matches!(
+            get_matrix().try_lt(&ArrayND::new(vec![1, 2])),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compare_scalar_and_transposed() {
+        let transposed = get_matrix().transpose();
+        assert_eq!(
+            transposed.gt_scalar(2).to_vec(),
+            vec![false, true, true, false, true, true]
+        );
+        assert_eq!(transposed.gt_scalar(2).shape(), &[3, 2]);
+    }
+
+    #[test]
+    fn compare_nan() {
+        let a = ArrayND::new(vec![f64::NAN, 1.]);
+        let b = ArrayND::new(vec![f64::NAN, 1.]);
+
+        assert_eq!(a.eq_elem(&b).to_vec(), vec![false, true]);
+        assert_eq!(a.ne_elem(&b).to_vec(), vec![true, false]);
+        assert_eq!(a.le_scalar(1.).to_vec(), vec![false, true]);
+    }
+
+    #[test]
+    fn select() {
+        let array = get_matrix();
+        let mask = array.gt_scalar(1) & array.lt_scalar(5);
+
+        assert_eq!(array.select(&mask).to_vec(), vec![3, 4, 2]);
+        assert_eq!(array.select(&!mask).to_vec(), vec![1, 5, 6]);
+        assert_eq!(array.select(&array.gt_scalar(10)).shape(), &[0]);
+    }
+
+    #[test]
+    fn select_shape_mismatch() {
+        let mask = ArrayND::new(vec![true, false, true]);
+        assert!(matches!(
+            get_matrix().try_select(&mask),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn mask_assign() {
+        let mut array = get_matrix();
+        assert_eq!(array.max(), 6);
+        array.mask_assign(&array.ge_scalar(5), 0);
+
+        assert_eq!(array.to_vec(), vec![1, 0, 3, 4, 2, 0]);
+        assert_eq!(array.max(), 4);
+    }
+
+    #[test]
+    fn mask_assign_broadcast() {
+        let mut array = get_matrix();
+        array.mask_assign(&ArrayND::new(vec![true, false, true]), -1);
+        assert_eq!(array.to_vec(), vec![-1, 5, -1, -1, 2, -1]);
+
+        let mut transposed = get_matrix().transpose();
+        transposed.mask_assign(&ArrayND::new(vec![false, true]), 0);
+        assert_eq!(transposed.to_vec(), vec![1, 0, 5, 0, 3, 0]);
+    }
+
+    #[test]
+    fn mask_assign_cannot_grow() {
+        let mut array = ArrayND::new(vec![1, 2, 3]);
+        let mask = ArrayND::from_shape_vec(vec![true; 6], vec![2, 3]);
+
+        assert!(matches!(
+            array.try_mask_assign(&mask, 0),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        assert_eq!(array.to_vec(), vec![1, 2, 3]);
+    }
+}
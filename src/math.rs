@@ -0,0 +1,258 @@
+//! Element-wise math functions on float Arrays
+//!
+//! Every function comes as a consuming method, which reuses the Array's
+//! buffer for the result, and as an in place `_mut` method. Functions of two
+//! arguments take a second Array, broadcast like the arithmetic operators, or
+//! a scalar with the `_scalar` methods.
+//!
+//! Large Arrays are processed on multiple threads like the arithmetic
+//! operators, see [`Parallelism`](crate::Parallelism).
+//!
+//! # Example
+//! ```
+//! use numrs::ArrayND;
+//! let array: ArrayND = ArrayND::new(vec![1., 4., 9.]);
+//! assert_eq!(array.sqrt().to_vec(), vec![1., 2., 3.]);
+//!
+//! let mut array: ArrayND = ArrayND::new(vec![-1.5, 0.5]);
+//! array.abs_mut();
+//! assert_eq!(array.pow_scalar(2.).to_vec(), vec![2.25, 0.25]);
+//! ```
+
+use num_traits::Float;
+
+use crate::{error, ops, ArrayND, Element, Result};
+
+/// -1, 0 or 1 depending on the sign of `x`, NaN for NaN
+fn sign<T: Float>(x: T) -> T {
+    if x > T::zero() {
+        T::one()
+    } else if x < T::zero() {
+        -T::one()
+    } else {
+        // Zero or NaN
+        x
+    }
+}
+
+/// Larger of two values, NaN if either is NaN
+fn maximum<T: Float>(x: T, y: T) -> T {
+    if Float::is_nan(x) || x >= y {
+        x
+    } else {
+        y
+    }
+}
+
+/// Smaller of two values, NaN if either is NaN
+fn minimum<T: Float>(x: T, y: T) -> T {
+    if Float::is_nan(x) || x <= y {
+        x
+    } else {
+        y
+    }
+}
+
+macro_rules! impl_unary {
+    ($($method:ident, $method_mut:ident, $f:expr, $doc:literal;)*) => {
+        impl<T: Element + Float> ArrayND<T> {
+            $(
+                #[doc = concat!($doc, " of every element")]
+                pub fn $method(mut self) -> ArrayND<T> {
+                    self.$method_mut();
+                    self
+                }
+
+                #[doc = concat!("Replaces every element with its ", $doc)]
+                pub fn $method_mut(&mut self) {
+                    self.map_assign(None, $f)
+                }
+            )*
+        }
+    };
+}
+
+impl_unary!(
+    exp, exp_mut, Float::exp, "`e^x`";
+    exp2, exp2_mut, Float::exp2, "`2^x`";
+    ln, ln_mut, Float::ln, "Natural logarithm";
+    log2, log2_mut, Float::log2, "Base 2 logarithm";
+    log10, log10_mut, Float::log10, "Base 10 logarithm";
+    log1p, log1p_mut, Float::ln_1p, "`ln(1 + x)`, accurate near zero,";
+    expm1, expm1_mut, Float::exp_m1, "`e^x - 1`, accurate near zero,";
+    sqrt, sqrt_mut, Float::sqrt, "Square root";
+    cbrt, cbrt_mut, Float::cbrt, "Cube root";
+    abs, abs_mut, Float::abs, "Absolute value";
+    sign, sign_mut, sign, "Sign (-1, 0 or 1, NaN for NaN)";
+    sin, sin_mut, Float::sin, "Sine";
+    cos, cos_mut, Float::cos, "Cosine";
+    tan, tan_mut, Float::tan, "Tangent";
+    asin, asin_mut, Float::asin, "Arcsine";
+    acos, acos_mut, Float::acos, "Arccosine";
+    atan, atan_mut, Float::atan, "Arctangent";
+    sinh, sinh_mut, Float::sinh, "Hyperbolic sine";
+    cosh, cosh_mut, Float::cosh, "Hyperbolic cosine";
+    tanh, tanh_mut, Float::tanh, "Hyperbolic tangent";
+    asinh, asinh_mut, Float::asinh, "Inverse hyperbolic sine";
+    acosh, acosh_mut, Float::acosh, "Inverse hyperbolic cosine";
+    atanh, atanh_mut, Float::atanh, "Inverse hyperbolic tangent";
+    floor, floor_mut, Float::floor, "Largest integer less than or equal";
+    ceil, ceil_mut, Float::ceil, "Smallest integer greater than or equal";
+    round, round_mut, Float::round, "Nearest integer, half-way cases away from zero,";
+    trunc, trunc_mut, Float::trunc, "Integer part";
+);
+
+macro_rules! impl_binary {
+    ($(
+        $method:ident, $try_method:ident, $method_mut:ident, $try_method_mut:ident,
+        $scalar_method:ident, $scalar_method_mut:ident, $f:expr, $doc:literal;
+    )*) => {
+        impl<T: Element + Float> ArrayND<T> {
+            $(
+                #[doc = concat!($doc, ", element-wise with broadcasting")]
+                ///
+                /// Panics if the shapes cannot be broadcast together.
+                pub fn $method(self, rhs: &ArrayND<T>) -> ArrayND<T> {
+                    error::or_panic(self.$try_method(rhs))
+                }
+
+                #[doc = concat!($doc, ", element-wise with broadcasting, failing if the")]
+                /// shapes are incompatible
+                pub fn $try_method(self, rhs: &ArrayND<T>) -> Result<ArrayND<T>> {
+                    ops::zip_into(self, &rhs.operand(), None, $f)
+                }
+
+                #[doc = concat!($doc, ", element-wise in place")]
+                ///
+                /// Panics if `rhs` cannot be broadcast to the shape of the Array.
+                pub fn $method_mut(&mut self, rhs: &ArrayND<T>) {
+                    error::or_panic(self.$try_method_mut(rhs))
+                }
+
+                #[doc = concat!($doc, ", element-wise in place, failing without")]
+                /// modifying the Array if `rhs` cannot be broadcast to its shape
+                pub fn $try_method_mut(&mut self, rhs: &ArrayND<T>) -> Result<()> {
+                    self.zip_assign(&rhs.operand(), None, $f)
+                }
+
+                #[doc = concat!($doc, " with a scalar second argument")]
+                pub fn $scalar_method(mut self, rhs: T) -> ArrayND<T> {
+                    self.$scalar_method_mut(rhs);
+                    self
+                }
+
+                #[doc = concat!($doc, " with a scalar second argument, in place")]
+                pub fn $scalar_method_mut(&mut self, rhs: T) {
+                    self.map_assign(None, move |x| $f(x, rhs))
+                }
+            )*
+        }
+    };
+}
+
+impl_binary!(
+    pow, try_pow, pow_mut, try_pow_mut, pow_scalar, pow_scalar_mut,
+    Float::powf, "`x^y`";
+    hypot, try_hypot, hypot_mut, try_hypot_mut, hypot_scalar, hypot_scalar_mut,
+    Float::hypot, "`sqrt(x^2 + y^2)` without overflow";
+    atan2, try_atan2, atan2_mut, try_atan2_mut, atan2_scalar, atan2_scalar_mut,
+    Float::atan2, "Four quadrant arctangent of `x / y`";
+    maximum, try_maximum, maximum_mut, try_maximum_mut, maximum_scalar, maximum_scalar_mut,
+    maximum, "Larger of `x` and `y`, NaN if either is NaN,";
+    minimum, try_minimum, minimum_mut, try_minimum_mut, minimum_scalar, minimum_scalar_mut,
+    minimum, "Smaller of `x` and `y`, NaN if either is NaN,";
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayND, NumrsError};
+
+    fn assert_close(array: ArrayND, expected: &[f64]) {
+        for (x, y) in array.to_vec().iter().zip(expected) {
+            assert!((x - y).abs() < 1e-12, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn unary() {
+        let array = ArrayND::new(vec![0.5, 1., 2.]);
+        assert_close(array.clone().exp().ln(), &[0.5, 1., 2.]);
+        assert_close(array.clone().exp2(), &[2f64.sqrt(), 2., 4.]);
+        assert_close(array.clone().log2(), &[-1., 0., 1.]);
+        assert_close(array.clone().sin().asin(), &[0.5, 1., 2f64.sin().asin()]);
+        assert_close(array.clone().tanh().atanh(), &[0.5, 1., 2.]);
+        assert_close(array.clone().cosh().acosh(), &[0.5, 1., 2.]);
+        assert_close(array.clone().pow_scalar(3.).cbrt(), &[0.5, 1., 2.]);
+
+        let tiny = ArrayND::new(vec![1e-20]);
+        assert_eq!(tiny.clone().log1p().to_vec(), vec![1e-20]);
+        assert_eq!(tiny.expm1().to_vec(), vec![1e-20]);
+    }
+
+    #[test]
+    fn sign_and_rounding() {
+        let array = ArrayND::new(vec![-2.5, -0.5, 0., 0.5, 2.5, f64::NAN]);
+        let sign = array.clone().sign().to_vec();
+        assert_eq!(sign[..5], [-1., -1., 0., 1., 1.]);
+        assert!(sign[5].is_nan());
+        assert_eq!(array.clone().round().to_vec()[..5], [-3., -1., 0., 1., 3.]);
+        assert_eq!(array.clone().floor().to_vec()[..5], [-3., -1., 0., 0., 2.]);
+        assert_eq!(array.clone().ceil().to_vec()[..5], [-2., -0., 0., 1., 3.]);
+        assert_eq!(array.trunc().to_vec()[..5], [-2., -0., 0., 0., 2.]);
+    }
+
+    #[test]
+    fn in_place() {
+        let mut array = ArrayND::new(vec![-4f32, 9.]);
+        array.abs_mut();
+        array.sqrt_mut();
+        assert_eq!(array.to_vec(), vec![2., 3.]);
+        assert_eq!(array.min(), 2.);
+
+        array.maximum_mut(&ArrayND::new(vec![2.5]));
+        assert_eq!(array.to_vec(), vec![2.5, 3.]);
+    }
+
+    #[test]
+    fn binary_broadcasting() {
+        let matrix = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+        let exponents = ArrayND::new(vec![2., 0.5]);
+        assert_close(matrix.clone().pow(&exponents), &[1., 2f64.sqrt(), 9., 2.]);
+
+        let column = ArrayND::from_shape_vec(vec![3., 4.], vec![2, 1]);
+        let grown = ArrayND::new(vec![4., 3.]).hypot(&column);
+        assert_eq!(grown.shape(), &[2, 2]);
+        assert_eq!(grown.to_vec(), vec![5., 3f64.hypot(3.), 4f64.hypot(4.), 5.]);
+
+        let y = ArrayND::new(vec![1., -1.]);
+        assert_close(
+            y.atan2_scalar(-1.),
+            &[0.75 * std::f64::consts::PI, -0.75 * std::f64::consts::PI],
+        );
+    }
+
+    #[test]
+    fn maximum_minimum_nan() {
+        let a = ArrayND::new(vec![1., f64::NAN, 3.]);
+        let b = ArrayND::new(vec![2., 0., f64::NAN]);
+        let max = a.clone().maximum(&b).to_vec();
+        assert_eq!(max[0], 2.);
+        assert!(max[1].is_nan() && max[2].is_nan());
+        let min = a.minimum(&b).to_vec();
+        assert_eq!(min[0], 1.);
+        assert!(min[1].is_nan() && min[2].is_nan());
+    }
+
+    #[test]
+    fn binary_errors() {
+        let array = ArrayND::new(vec![1., 2., 3.]);
+        assert!(matches!(
+            array.clone().try_pow(&ArrayND::new(vec![1., 2.])),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        let mut row = array.clone();
+        let matrix = ArrayND::from_shape_vec(vec![1.; 6], vec![2, 3]);
+        assert!(row.try_minimum_mut(&matrix).is_err());
+        assert_eq!(row, array);
+    }
+}
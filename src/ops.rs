@@ -0,0 +1,986 @@
+//! Arithmetic and bitwise operators for `ArrayND`
+//!
+//! Every operator is implemented for owned and borrowed Arrays, Vecs and
+//! scalars of any element type supporting it. On bool Arrays `&`, `|`, `^`
+//! and `!` are the logical operators used to combine masks. When one side is owned and
+//! already has the broadcast shape its buffer is reused for the result
+//! instead of allocating a new one.
+//!
+//! Integer `+`, `-` and `*` wrap around on overflow, see
+//! [`Element::wrapping_add`]. The operators panic when the shapes cannot be
+//! broadcast together or an integer division has a zero divisor, the `try_`
+//! methods return a `NumrsError` instead.
+//!
+//! Element-wise work on contiguous buffers is a single fused pass: the
+//! compound assignment operators and the owned forms above write every result
+//! straight into the reused buffer, the borrowed forms into one new buffer,
+//! without intermediate Vecs. Once the Array is larger than the configured
+//! threshold the pass is split into chunks across threads, see
+//! [`Parallelism`](crate::Parallelism).
+
+#[cfg(target_family = "unix")]
+use rayon::{
+    iter::{IndexedParallelIterator, ParallelIterator},
+    slice::{ParallelSlice, ParallelSliceMut},
+};
+use std::{
+    ops::{
+        Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div,
+        DivAssign, Mul, MulAssign, Not, Sub, SubAssign,
+    },
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+use crate::{
+    broadcast, error, layout, parallel,
+    simd::{self, Arith},
+    ArrayND, Element, NumrsError, Result,
+};
+
+/// Borrowed buffer, start offset, shape and strides of one side of a binary operation
+pub(crate) struct Operand<'a, T> {
+    data: &'a [T],
+    offset: usize,
+    shape: &'a [usize],
+    strides: &'a [isize],
+}
+
+impl<T: Element> ArrayND<T> {
+    pub(crate) fn operand(&self) -> Operand<'_, T> {
+        Operand {
+            data: &self.data,
+            offset: 0,
+            shape: &self.shape,
+            strides: &self.strides,
+        }
+    }
+}
+
+impl<T> Operand<'_, T> {
+    /// Whether the operand is a row-major buffer with exactly the given shape
+    fn is_contiguous(&self, shape: &[usize]) -> bool {
+        self.shape == shape
+            && self.offset == 0
+            && self.data.len() == shape.iter().product::<usize>()
+            && layout::is_row_major(shape, self.strides)
+    }
+}
+
+/// Shape of the result of a binary operation, failing if the operands are incompatible
+pub(crate) fn result_shape(lhs: &[usize], rhs: &[usize]) -> Result<Vec<usize>> {
+    broadcast::broadcast_shapes(lhs, rhs).ok_or_else(|| {
+        NumrsError::ShapeMismatch(format!(
+            "operands could not be broadcast together with shapes {:?} {:?}",
+            lhs, rhs
+        ))
+    })
+}
+
+/// Combines two borrowed operands into a newly allocated row-major Array
+///
+/// Operands that are both contiguous with the result shape are combined on
+/// multiple threads when large enough, with the vector kernel of `arith` if
+/// there is one for the element type.
+pub(crate) fn zip_new<T: Element, U: Element>(
+    lhs: &Operand<T>,
+    rhs: &Operand<T>,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> U + Sync + Send,
+) -> Result<ArrayND<U>> {
+    let shape = result_shape(lhs.shape, rhs.shape)?;
+    let size: usize = shape.iter().product();
+    if lhs.is_contiguous(&shape) && rhs.is_contiguous(&shape) {
+        let data = parallel::run(size, |parallel| {
+            zip_slices(lhs.data, rhs.data, parallel, arith, op)
+        });
+        return ArrayND::try_from_shape_vec(data, shape);
+    }
+    let lhs_strides = broadcast::broadcast_strides(lhs.shape, lhs.strides, &shape);
+    let rhs_strides = broadcast::broadcast_strides(rhs.shape, rhs.strides, &shape);
+    let data: Vec<U> = layout::Offsets::new(lhs.offset, &shape, &lhs_strides)
+        .zip(layout::Offsets::new(rhs.offset, &shape, &rhs_strides))
+        .map(|(i, j)| op(lhs.data[i], rhs.data[j]))
+        .collect();
+    ArrayND::try_from_shape_vec(data, shape)
+}
+
+/// Combines an owned Array with a borrowed operand, reusing the Array's buffer
+/// when it already has the broadcast shape
+pub(crate) fn zip_into<T: Element>(
+    mut lhs: ArrayND<T>,
+    rhs: &Operand<T>,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) -> Result<ArrayND<T>> {
+    if result_shape(&lhs.shape, rhs.shape)? != lhs.shape {
+        return zip_new(&lhs.operand(), rhs, arith, op);
+    }
+    lhs.zip_assign(rhs, arith, op)?;
+    Ok(lhs)
+}
+
+/// Writes `op` between two borrowed operands into `out`, only where `mask` is
+/// `true` if there is one
+///
+/// The operands and the mask are broadcast to the shape of `out`, failing
+/// without writing anything if they cannot be. Contiguous operands are
+/// combined on multiple threads when large enough.
+pub(crate) fn zip_to<T: Element>(
+    lhs: &Operand<T>,
+    rhs: &Operand<T>,
+    out: &mut ArrayND<T>,
+    mask: Option<&Operand<bool>>,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) -> Result<()> {
+    let shape = result_shape(lhs.shape, rhs.shape)?;
+    if result_shape(&shape, &out.shape)? != out.shape {
+        return Err(NumrsError::ShapeMismatch(format!(
+            "could not broadcast shape {:?} into output of shape {:?}",
+            shape, out.shape
+        )));
+    }
+    if let Some(mask) = mask {
+        if result_shape(mask.shape, &out.shape)? != out.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "could not broadcast mask of shape {:?} into shape {:?}",
+                mask.shape, out.shape
+            )));
+        }
+    }
+
+    let contiguous = out.operand().is_contiguous(&out.shape)
+        && lhs.is_contiguous(&out.shape)
+        && rhs.is_contiguous(&out.shape)
+        && mask.is_none_or(|mask| mask.is_contiguous(&out.shape));
+    if contiguous {
+        let (data, mask) = (&mut out.data, mask.map(|mask| mask.data));
+        parallel::run(data.len(), |parallel| {
+            zip_slices_to(data, lhs.data, rhs.data, mask, parallel, arith, op)
+        });
+    } else {
+        let lhs_strides = broadcast::broadcast_strides(lhs.shape, lhs.strides, &out.shape);
+        let rhs_strides = broadcast::broadcast_strides(rhs.shape, rhs.strides, &out.shape);
+        let positions = layout::Offsets::new(0, &out.shape, &out.strides)
+            .zip(layout::Offsets::new(lhs.offset, &out.shape, &lhs_strides))
+            .zip(layout::Offsets::new(rhs.offset, &out.shape, &rhs_strides));
+        match mask {
+            None => {
+                for ((k, i), j) in positions {
+                    out.data[k] = op(lhs.data[i], rhs.data[j]);
+                }
+            }
+            Some(mask) => {
+                let mask_strides =
+                    broadcast::broadcast_strides(mask.shape, mask.strides, &out.shape);
+                let mask_offsets = layout::Offsets::new(mask.offset, &out.shape, &mask_strides);
+                for (((k, i), j), m) in positions.zip(mask_offsets) {
+                    if mask.data[m] {
+                        out.data[k] = op(lhs.data[i], rhs.data[j]);
+                    }
+                }
+            }
+        }
+    }
+    out.invalidate_stats();
+    Ok(())
+}
+
+/// Combines two owned Arrays, reusing whichever buffer already has the broadcast shape
+fn zip_owned<T: Element>(
+    lhs: ArrayND<T>,
+    rhs: ArrayND<T>,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) -> Result<ArrayND<T>> {
+    if lhs.shape != rhs.shape && result_shape(&lhs.shape, &rhs.shape)? == rhs.shape {
+        zip_into(rhs, &lhs.operand(), arith.map(Arith::swapped), |y, x| {
+            op(x, y)
+        })
+    } else {
+        zip_into(lhs, &rhs.operand(), arith, op)
+    }
+}
+
+/// Combines a borrowed Array with an owned one, reusing the owned buffer when
+/// it already has the broadcast shape
+fn zip_ref_owned<T: Element>(
+    lhs: &ArrayND<T>,
+    rhs: ArrayND<T>,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) -> Result<ArrayND<T>> {
+    if result_shape(&lhs.shape, &rhs.shape)? == rhs.shape {
+        zip_into(rhs, &lhs.operand(), arith.map(Arith::swapped), |y, x| {
+            op(x, y)
+        })
+    } else {
+        zip_new(&lhs.operand(), &rhs.operand(), arith, op)
+    }
+}
+
+/// The vector kernel `arith` with its scalar right operand
+fn with_scalar<T>(arith: Option<Arith>, scalar: T) -> Option<(Arith, T)> {
+    arith.map(|arith| (arith, scalar))
+}
+
+/// Applies `op` to every element of an owned Array in place
+fn map_into<T: Element>(
+    mut array: ArrayND<T>,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) -> ArrayND<T> {
+    array.map_assign(scalar, op);
+    array
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Applies `op` in place between every element and the matching element of `rhs`
+    ///
+    /// Fails without modifying the Array if `rhs` cannot be broadcast to its shape.
+    pub(crate) fn zip_assign(
+        &mut self,
+        rhs: &Operand<T>,
+        arith: Option<Arith>,
+        op: impl Fn(T, T) -> T + Sync + Send,
+    ) -> Result<()> {
+        let shape = result_shape(&self.shape, rhs.shape)?;
+        if shape != self.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "could not broadcast shape {:?} into shape {:?}",
+                rhs.shape, self.shape
+            )));
+        }
+        let rhs_strides = broadcast::broadcast_strides(rhs.shape, rhs.strides, &shape);
+
+        if rhs.offset == 0 && rhs_strides == self.strides && rhs.data.len() == self.data.len() {
+            let (lhs, rhs) = (&mut self.data, rhs.data);
+            parallel::run(lhs.len(), |parallel| {
+                zip_in_place(lhs, rhs, parallel, arith, op)
+            });
+        } else {
+            let lhs_offsets = layout::Offsets::new(0, &self.shape, &self.strides);
+            let rhs_offsets = layout::Offsets::new(rhs.offset, &shape, &rhs_strides);
+            for (i, j) in lhs_offsets.zip(rhs_offsets) {
+                self.data[i] = op(self.data[i], rhs.data[j]);
+            }
+        }
+        self.invalidate_stats();
+        Ok(())
+    }
+
+    /// Applies `op` to every element in place
+    pub(crate) fn map_assign(
+        &mut self,
+        scalar: Option<(Arith, T)>,
+        op: impl Fn(T) -> T + Sync + Send,
+    ) {
+        let data = &mut self.data;
+        parallel::run(data.len(), |parallel| {
+            map_in_place(data, parallel, scalar, op)
+        });
+        self.invalidate_stats();
+    }
+}
+
+/// Elements per chunk handed to a thread by the multi-threaded kernels
+#[cfg(target_family = "unix")]
+const CHUNK: usize = 1 << 14;
+
+/// Applies `op` between every element of `lhs` and the matching element of
+/// `rhs`, writing the result into `lhs`
+#[cfg(target_family = "wasm")]
+fn zip_in_place<T: Element>(
+    lhs: &mut [T],
+    rhs: &[T],
+    _parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) {
+    zip_chunk(lhs, rhs, arith, &op);
+}
+
+/// Applies `op` between every element of `lhs` and the matching element of
+/// `rhs`, writing the result into `lhs`, on multiple threads if `parallel` is set
+#[cfg(target_family = "unix")]
+fn zip_in_place<T: Element>(
+    lhs: &mut [T],
+    rhs: &[T],
+    parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) {
+    if parallel {
+        lhs.par_chunks_mut(CHUNK)
+            .zip(rhs.par_chunks(CHUNK))
+            .for_each(|(lhs, rhs)| zip_chunk(lhs, rhs, arith, &op));
+    } else {
+        zip_chunk(lhs, rhs, arith, &op);
+    }
+}
+
+/// Applies `op` in place, with the vector kernel of `arith` if there is one for `T`
+fn zip_chunk<T: Element>(lhs: &mut [T], rhs: &[T], arith: Option<Arith>, op: &impl Fn(T, T) -> T) {
+    if arith.is_some_and(|arith| simd::zip_assign(arith, lhs, rhs)) {
+        return;
+    }
+    for (x, &y) in lhs.iter_mut().zip(rhs) {
+        *x = op(*x, y);
+    }
+}
+
+/// Applies `op` to every element of `data` in place
+#[cfg(target_family = "wasm")]
+pub(crate) fn map_in_place<T: Element>(
+    data: &mut [T],
+    _parallel: bool,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) {
+    map_chunk(data, scalar, &op);
+}
+
+/// Applies `op` to every element of `data` in place, on multiple threads if
+/// `parallel` is set
+#[cfg(target_family = "unix")]
+pub(crate) fn map_in_place<T: Element>(
+    data: &mut [T],
+    parallel: bool,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) {
+    if parallel {
+        data.par_chunks_mut(CHUNK)
+            .for_each(|chunk| map_chunk(chunk, scalar, &op));
+    } else {
+        map_chunk(data, scalar, &op);
+    }
+}
+
+/// Applies `op` in place, with the vector kernel of `arith` and the scalar
+/// operand if there is one for `T`
+fn map_chunk<T: Element>(data: &mut [T], scalar: Option<(Arith, T)>, op: &impl Fn(T) -> T) {
+    if scalar.is_some_and(|(arith, scalar)| simd::map_assign(arith, data, scalar)) {
+        return;
+    }
+    for x in data {
+        *x = op(*x);
+    }
+}
+
+/// Applies `op` between matching elements of `lhs` and `rhs` into a new buffer
+#[cfg(target_family = "wasm")]
+fn zip_slices<T: Element, U: Element>(
+    lhs: &[T],
+    rhs: &[T],
+    _parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> U + Sync + Send,
+) -> Vec<U> {
+    let mut out = vec![U::zero(); lhs.len()];
+    zip_chunk_into(&mut out, lhs, rhs, arith, &op);
+    out
+}
+
+/// Applies `op` between matching elements of `lhs` and `rhs` into a new
+/// buffer, on multiple threads if `parallel` is set
+#[cfg(target_family = "unix")]
+fn zip_slices<T: Element, U: Element>(
+    lhs: &[T],
+    rhs: &[T],
+    parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> U + Sync + Send,
+) -> Vec<U> {
+    let mut out = vec![U::zero(); lhs.len()];
+    if parallel {
+        out.par_chunks_mut(CHUNK)
+            .zip(lhs.par_chunks(CHUNK))
+            .zip(rhs.par_chunks(CHUNK))
+            .for_each(|((out, lhs), rhs)| zip_chunk_into(out, lhs, rhs, arith, &op));
+    } else {
+        zip_chunk_into(&mut out, lhs, rhs, arith, &op);
+    }
+    out
+}
+
+/// Writes `op` between matching elements into `out`, with the vector kernel
+/// of `arith` if there is one for the types
+fn zip_chunk_into<T: Element, U: Element>(
+    out: &mut [U],
+    lhs: &[T],
+    rhs: &[T],
+    arith: Option<Arith>,
+    op: &impl Fn(T, T) -> U,
+) {
+    if arith.is_some_and(|arith| simd::zip(arith, lhs, rhs, out)) {
+        return;
+    }
+    for ((z, &x), &y) in out.iter_mut().zip(lhs).zip(rhs) {
+        *z = op(x, y);
+    }
+}
+
+/// Writes `op` between matching elements of `lhs` and `rhs` into `out`, where `mask` is set
+#[cfg(target_family = "wasm")]
+fn zip_slices_to<T: Element>(
+    out: &mut [T],
+    lhs: &[T],
+    rhs: &[T],
+    mask: Option<&[bool]>,
+    _parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) {
+    zip_chunk_to(out, lhs, rhs, mask, arith, &op);
+}
+
+/// Writes `op` between matching elements of `lhs` and `rhs` into `out`, where
+/// `mask` is set, on multiple threads if `parallel` is set
+#[cfg(target_family = "unix")]
+fn zip_slices_to<T: Element>(
+    out: &mut [T],
+    lhs: &[T],
+    rhs: &[T],
+    mask: Option<&[bool]>,
+    parallel: bool,
+    arith: Option<Arith>,
+    op: impl Fn(T, T) -> T + Sync + Send,
+) {
+    if parallel {
+        out.par_chunks_mut(CHUNK)
+            .zip(lhs.par_chunks(CHUNK))
+            .zip(rhs.par_chunks(CHUNK))
+            .enumerate()
+            .for_each(|(chunk, ((out, lhs), rhs))| {
+                let mask = mask.map(|mask| &mask[chunk * CHUNK..][..out.len()]);
+                zip_chunk_to(out, lhs, rhs, mask, arith, &op)
+            });
+    } else {
+        zip_chunk_to(out, lhs, rhs, mask, arith, &op);
+    }
+}
+
+/// Writes `op` between matching elements into `out` where `mask` is set,
+/// with the vector kernel of `arith` when there is no mask
+fn zip_chunk_to<T: Element>(
+    out: &mut [T],
+    lhs: &[T],
+    rhs: &[T],
+    mask: Option<&[bool]>,
+    arith: Option<Arith>,
+    op: &impl Fn(T, T) -> T,
+) {
+    let Some(mask) = mask else {
+        return zip_chunk_into(out, lhs, rhs, arith, op);
+    };
+    for (((z, &x), &y), &keep) in out.iter_mut().zip(lhs).zip(rhs).zip(mask) {
+        if keep {
+            *z = op(x, y);
+        }
+    }
+}
+
+/// Applies `op` to every element of `data` into a new buffer
+#[cfg(target_family = "wasm")]
+fn map_slice<T: Element>(
+    data: &[T],
+    _parallel: bool,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) -> Vec<T> {
+    let mut out = vec![T::zero(); data.len()];
+    map_chunk_into(&mut out, data, scalar, &op);
+    out
+}
+
+/// Applies `op` to every element of `data` into a new buffer, on multiple
+/// threads if `parallel` is set
+#[cfg(target_family = "unix")]
+fn map_slice<T: Element>(
+    data: &[T],
+    parallel: bool,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) -> Vec<T> {
+    let mut out = vec![T::zero(); data.len()];
+    if parallel {
+        out.par_chunks_mut(CHUNK)
+            .zip(data.par_chunks(CHUNK))
+            .for_each(|(out, data)| map_chunk_into(out, data, scalar, &op));
+    } else {
+        map_chunk_into(&mut out, data, scalar, &op);
+    }
+    out
+}
+
+/// Writes `op` of every element into `out`, with the vector kernel of
+/// `arith` and the scalar operand if there is one for `T`
+fn map_chunk_into<T: Element>(
+    out: &mut [T],
+    data: &[T],
+    scalar: Option<(Arith, T)>,
+    op: &impl Fn(T) -> T,
+) {
+    if scalar.is_some_and(|(arith, scalar)| simd::map(arith, data, scalar, out)) {
+        return;
+    }
+    for (z, &x) in out.iter_mut().zip(data) {
+        *z = op(x);
+    }
+}
+
+/// Applies `op` to every element of a borrowed Array, keeping its memory layout
+pub(crate) fn map_new<T: Element>(
+    array: &ArrayND<T>,
+    scalar: Option<(Arith, T)>,
+    op: impl Fn(T) -> T + Sync + Send,
+) -> ArrayND<T> {
+    ArrayND {
+        data: parallel::run(array.size, |parallel| {
+            map_slice(&array.data, parallel, scalar, op)
+        }),
+        shape: array.shape.clone(),
+        strides: array.strides.clone(),
+        size: array.size,
+        stats: OnceLock::new(),
+    }
+}
+
+/// `$x $op $y`, with the wrapping methods of `Element` for `+`, `-` and `*`
+macro_rules! apply {
+    ($x:expr, +, $y:expr) => {
+        Element::wrapping_add($x, $y)
+    };
+    ($x:expr, -, $y:expr) => {
+        Element::wrapping_sub($x, $y)
+    };
+    ($x:expr, *, $y:expr) => {
+        Element::wrapping_mul($x, $y)
+    };
+    ($x:expr, $op:tt, $y:expr) => {
+        $x $op $y
+    };
+}
+
+/// Operators between Arrays, Vecs and scalars, with the vector kernel `$arith`
+/// for the element types that have one
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $op:tt, $arith:expr) => {
+        impl<T: Element + $trait<Output = T>> $trait<ArrayND<T>> for ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: ArrayND<T>) -> ArrayND<T> {
+                error::or_panic(zip_owned(self, rhs, $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<&ArrayND<T>> for ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: &ArrayND<T>) -> ArrayND<T> {
+                error::or_panic(zip_into(self, &rhs.operand(), $arith, |x, y| {
+                    apply!(x, $op, y)
+                }))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<ArrayND<T>> for &ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: ArrayND<T>) -> ArrayND<T> {
+                error::or_panic(zip_ref_owned(self, rhs, $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<&ArrayND<T>> for &ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: &ArrayND<T>) -> ArrayND<T> {
+                error::or_panic(zip_new(&self.operand(), &rhs.operand(), $arith, |x, y| {
+                    apply!(x, $op, y)
+                }))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<Vec<T>> for ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: Vec<T>) -> ArrayND<T> {
+                let rhs = Operand {
+                    data: &rhs,
+                    offset: 0,
+                    shape: &[rhs.len()],
+                    strides: &[1],
+                };
+                error::or_panic(zip_into(self, &rhs, $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<T> for ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: T) -> ArrayND<T> {
+                map_into(self, with_scalar($arith, rhs), |x| apply!(x, $op, rhs))
+            }
+        }
+
+        impl<T: Element + $trait<Output = T>> $trait<T> for &ArrayND<T> {
+            type Output = ArrayND<T>;
+
+            fn $method(self, rhs: T) -> ArrayND<T> {
+                map_new(self, with_scalar($arith, rhs), |x| apply!(x, $op, rhs))
+            }
+        }
+    };
+}
+
+/// Operators with a scalar on the left, which have to be implemented per element type
+macro_rules! impl_scalar_lhs_op {
+    ($trait:ident, $method:ident, $op:tt, $arith:expr, $($t:ty),*) => {
+        $(
+            impl $trait<ArrayND<$t>> for $t {
+                type Output = ArrayND<$t>;
+
+                fn $method(self, rhs: ArrayND<$t>) -> ArrayND<$t> {
+                    map_into(rhs, Some(($arith.swapped(), self)), |y| apply!(self, $op, y))
+                }
+            }
+
+            impl $trait<&ArrayND<$t>> for $t {
+                type Output = ArrayND<$t>;
+
+                fn $method(self, rhs: &ArrayND<$t>) -> ArrayND<$t> {
+                    map_new(rhs, Some(($arith.swapped(), self)), |y| apply!(self, $op, y))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $method:ident, $op:tt, $bound:ident, $arith:expr) => {
+        impl<T: Element + $bound<Output = T>> $trait<T> for ArrayND<T> {
+            fn $method(&mut self, rhs: T) {
+                self.map_assign(with_scalar($arith, rhs), |x| apply!(x, $op, rhs))
+            }
+        }
+
+        impl<T: Element + $bound<Output = T>> $trait<ArrayND<T>> for ArrayND<T> {
+            fn $method(&mut self, rhs: ArrayND<T>) {
+                error::or_panic(self.zip_assign(&rhs.operand(), $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+
+        impl<T: Element + $bound<Output = T>> $trait<&ArrayND<T>> for ArrayND<T> {
+            fn $method(&mut self, rhs: &ArrayND<T>) {
+                error::or_panic(self.zip_assign(&rhs.operand(), $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+
+        impl<T: Element + $bound<Output = T>> $trait<&[T]> for ArrayND<T> {
+            fn $method(&mut self, rhs: &[T]) {
+                let rhs = Operand {
+                    data: rhs,
+                    offset: 0,
+                    shape: &[rhs.len()],
+                    strides: &[1],
+                };
+                error::or_panic(self.zip_assign(&rhs, $arith, |x, y| apply!(x, $op, y)))
+            }
+        }
+    };
+}
+
+/// Fallible counterparts of the operators, failing instead of panicking when
+/// the shapes cannot be broadcast together
+macro_rules! impl_try_op {
+    ($bound:ident, $op:tt, $method:ident, $assign_method:ident, $name:literal, $arith:expr) => {
+        impl<T: Element + $bound<Output = T>> ArrayND<T> {
+            #[doc = concat!("Element-wise `", $name, "` with broadcasting, failing if the shapes are incompatible")]
+            pub fn $method(&self, rhs: &ArrayND<T>) -> Result<ArrayND<T>> {
+                zip_new(&self.operand(), &rhs.operand(), $arith, |x, y| apply!(x, $op, y))
+            }
+
+            #[doc = concat!("In place `", $name, "=` with broadcasting, failing without")]
+            /// modifying the Array if `rhs` cannot be broadcast to its shape
+            pub fn $assign_method(&mut self, rhs: &ArrayND<T>) -> Result<()> {
+                self.zip_assign(&rhs.operand(), $arith, |x, y| apply!(x, $op, y))
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, +, Some(Arith::Add));
+impl_binary_op!(Sub, sub, -, Some(Arith::Sub));
+impl_binary_op!(Mul, mul, *, Some(Arith::Mul));
+impl_binary_op!(Div, div, /, Some(Arith::Div));
+impl_binary_op!(BitAnd, bitand, &, None);
+impl_binary_op!(BitOr, bitor, |, None);
+impl_binary_op!(BitXor, bitxor, ^, None);
+
+impl_scalar_lhs_op!(Add, add, +, Arith::Add, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+impl_scalar_lhs_op!(Sub, sub, -, Arith::Sub, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+impl_scalar_lhs_op!(Mul, mul, *, Arith::Mul, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+impl_scalar_lhs_op!(Div, div, /, Arith::Div, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl_assign_op!(AddAssign, add_assign, +, Add, Some(Arith::Add));
+impl_assign_op!(SubAssign, sub_assign, -, Sub, Some(Arith::Sub));
+impl_assign_op!(MulAssign, mul_assign, *, Mul, Some(Arith::Mul));
+impl_assign_op!(DivAssign, div_assign, /, Div, Some(Arith::Div));
+impl_assign_op!(BitAndAssign, bitand_assign, &, BitAnd, None);
+impl_assign_op!(BitOrAssign, bitor_assign, |, BitOr, None);
+impl_assign_op!(BitXorAssign, bitxor_assign, ^, BitXor, None);
+
+impl_try_op!(Add, +, try_add, try_add_assign, "+", Some(Arith::Add));
+impl_try_op!(Sub, -, try_sub, try_sub_assign, "-", Some(Arith::Sub));
+impl_try_op!(Mul, *, try_mul, try_mul_assign, "*", Some(Arith::Mul));
+impl_try_op!(BitAnd, &, try_bitand, try_bitand_assign, "&", None);
+impl_try_op!(BitOr, |, try_bitor, try_bitor_assign, "|", None);
+impl_try_op!(BitXor, ^, try_bitxor, try_bitxor_assign, "^", None);
+
+/// `x / y`, raising `failed` instead of panicking if an integer division has
+/// a zero divisor or overflows
+fn flagged_div<T: Element>(x: T, y: T, failed: &AtomicBool) -> T {
+    x.checked_div(y).unwrap_or_else(|| {
+        failed.store(true, Ordering::Relaxed);
+        T::zero()
+    })
+}
+
+/// Runs `f` with a flag for `flagged_div`, failing if it was raised
+fn checked<R>(f: impl FnOnce(&AtomicBool) -> Result<R>) -> Result<R> {
+    let failed = AtomicBool::new(false);
+    let result = f(&failed)?;
+    if failed.into_inner() {
+        return Err(NumrsError::Arithmetic(
+            "integer division by zero or overflow".to_string(),
+        ));
+    }
+    Ok(result)
+}
+
+/// Like `zip_into` with `/`, failing if an integer division has a zero
+/// divisor or overflows, like `i32::MIN / -1`
+pub(crate) fn div_into<T: Element>(lhs: ArrayND<T>, rhs: &Operand<T>) -> Result<ArrayND<T>> {
+    checked(|failed| zip_into(lhs, rhs, Some(Arith::Div), |x, y| flagged_div(x, y, failed)))
+}
+
+impl<T: Element + Div<Output = T>> ArrayND<T> {
+    /// Element-wise `/` with broadcasting, failing if the shapes are
+    /// incompatible or an integer division has a zero divisor or overflows
+    pub fn try_div(&self, rhs: &ArrayND<T>) -> Result<ArrayND<T>> {
+        checked(|failed| {
+            zip_new(&self.operand(), &rhs.operand(), Some(Arith::Div), |x, y| {
+                flagged_div(x, y, failed)
+            })
+        })
+    }
+
+    /// In place `/=` with broadcasting, failing without modifying the Array if
+    /// `rhs` cannot be broadcast to its shape or an integer division has a
+    /// zero divisor or overflows
+    pub fn try_div_assign(&mut self, rhs: &ArrayND<T>) -> Result<()> {
+        if T::DTYPE.is_float() {
+            return self.zip_assign(&rhs.operand(), Some(Arith::Div), |x, y| x / y);
+        }
+        // Integer quotients are all checked before any element is overwritten
+        let quotient = self.try_div(rhs)?;
+        self.zip_assign(&quotient.operand(), None, |_, q| q)
+    }
+}
+
+impl<T: Element + Not<Output = T>> Not for ArrayND<T> {
+    type Output = ArrayND<T>;
+
+    fn not(self) -> ArrayND<T> {
+        map_into(self, None, |x| !x)
+    }
+}
+
+impl<T: Element + Not<Output = T>> Not for &ArrayND<T> {
+    type Output = ArrayND<T>;
+
+    fn not(self) -> ArrayND<T> {
+        map_new(self, None, |x| !x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{with_parallelism, ArrayND, Parallelism};
+
+    fn get_matrix() -> ArrayND {
+        ArrayND::arange(1., 7., 1.).reshape(&[2, 3])
+    }
+
+    #[test]
+    fn ref_ref() {
+        let array1 = get_matrix();
+        let array2 = get_matrix();
+
+        assert_eq!(&array1 + &array2, get_matrix() * 2.);
+        assert_eq!(&array1 - &array2, get_matrix() * 0.);
+        assert_eq!(&array1 * &array2, get_matrix() * get_matrix());
+        assert_eq!(&array1 / &array2, get_matrix() / get_matrix());
+        assert_eq!(array1, get_matrix());
+    }
+
+    #[test]
+    fn mixed_owned_ref() {
+        let array = get_matrix();
+        let row = ArrayND::new(vec![1., 2., 3.]);
+
+        assert_eq!(
+            get_matrix() - &row,
+            ArrayND::from_shape_vec(vec![0., 0., 0., 3., 3., 3.], vec![2, 3])
+        );
+        assert_eq!(
+            &row - get_matrix(),
+            ArrayND::from_shape_vec(vec![0., 0., 0., -3., -3., -3.], vec![2, 3])
+        );
+        assert_eq!(&array / row.clone(), get_matrix() / row.clone());
+        assert_eq!(row.clone() / &array, row.clone() / get_matrix());
+        assert_eq!(row.clone() - get_matrix(), &row - &array);
+    }
+
+    #[test]
+    fn scalar_ref() {
+        let array = get_matrix();
+
+        assert_eq!(&array + 1., get_matrix() + 1.);
+        assert_eq!(&array * 2., 2. * &array);
+        assert_eq!(&array / 2., get_matrix() * 0.5);
+        assert_eq!(
+            10. - &array,
+            ArrayND::from_shape_vec(vec![9., 8., 7., 6., 5., 4.], vec![2, 3])
+        );
+        assert_eq!(
+            6. / get_matrix(),
+            ArrayND::from_shape_vec(vec![6., 3., 2., 1.5, 1.2, 1.], vec![2, 3])
+        );
+        assert_eq!(1. + get_matrix(), &array + 1.);
+    }
+
+    #[test]
+    fn ref_ops_keep_layout() {
+        let transposed = get_matrix().transpose();
+        let result = &transposed * 2.;
+
+        assert_eq!(result.strides(), transposed.strides());
+        assert_eq!(result.to_vec(), vec![2., 8., 4., 10., 6., 12.]);
+        assert_eq!(&transposed + &transposed, result);
+    }
+
+    #[test]
+    fn ops_update_stats() {
+        let result = -1. * get_matrix();
+        assert_eq!(result.min(), -6.);
+        assert_eq!(result.max(), -1.);
+
+        let result = &get_matrix() - &(get_matrix() * 2.);
+        assert_eq!(result.min(), -6.);
+        assert_eq!(result.max(), -1.);
+    }
+
+    #[test]
+    fn assign_scalar() {
+        let mut array = get_matrix();
+        array += 1.;
+        array *= -2.;
+        array -= 1.;
+        array /= 2.;
+
+        assert_eq!(
+            array,
+            ArrayND::from_shape_vec(vec![-2.5, -3.5, -4.5, -5.5, -6.5, -7.5], vec![2, 3])
+        );
+        assert_eq!(array.min(), -7.5);
+        assert_eq!(array.max(), -2.5);
+    }
+
+    #[test]
+    fn assign_array() {
+        let mut array = get_matrix();
+        array += get_matrix();
+        array -= &ArrayND::new(vec![1., 1., 1.]);
+        array *= &[1., 0., -1.][..];
+        array /= ArrayND::from_shape_vec(vec![1., 2.], vec![2, 1]);
+
+        assert_eq!(
+            array,
+            ArrayND::from_shape_vec(vec![1., 0., -5., 3.5, 0., -5.5], vec![2, 3])
+        );
+        assert_eq!(array.min(), -5.5);
+        assert_eq!(array.max(), 3.5);
+    }
+
+    #[test]
+    fn assign_transposed() {
+        let mut array = get_matrix().transpose();
+        array += &get_matrix().transpose();
+
+        assert_eq!(array, get_matrix().transpose() * 2.);
+    }
+
+    #[test]
+    #[should_panic(expected = "could not broadcast")]
+    fn assign_cannot_grow() {
+        let mut array = ArrayND::new(vec![1., 2., 3.]);
+        array += get_matrix();
+    }
+
+    #[test]
+    fn assign_parallel() {
+        let mut array = ArrayND::new(vec![1.; 1_000_001]);
+        array *= 3.;
+        array += &ArrayND::new(vec![-1.; 1_000_001]);
+
+        assert_eq!(array.sum(), 2_000_002.);
+        assert_eq!(array.min(), 2.);
+        assert_eq!(array.max(), 2.);
+    }
+
+    #[test]
+    fn chunked_kernels_match_sequential() {
+        let a: ArrayND = ArrayND::random(3 * super::CHUNK + 5);
+        let b: ArrayND = ArrayND::random(3 * super::CHUNK + 5);
+        let kernels = || {
+            let mut assigned = a.clone();
+            assigned -= &b;
+            (
+                (&a + &b).to_vec(),
+                (a.clone() * &b).to_vec(),
+                assigned.to_vec(),
+            )
+        };
+        let expected = with_parallelism(&Parallelism::new().with_threshold(usize::MAX), kernels);
+        let config = Parallelism::new()
+            .with_threshold(0)
+            .with_threads(3)
+            .unwrap();
+        assert_eq!(with_parallelism(&config, kernels), expected);
+    }
+
+    #[test]
+    fn bitwise() {
+        let a = ArrayND::new(vec![true, true, false, false]);
+        let b = ArrayND::new(vec![true, false, true, false]);
+
+        assert_eq!((&a & &b).to_vec(), vec![true, false, false, false]);
+        assert_eq!((&a | &b).to_vec(), vec![true, true, true, false]);
+        assert_eq!((&a ^ b.clone()).to_vec(), vec![false, true, true, false]);
+        assert_eq!((!a.clone()).to_vec(), vec![false, false, true, true]);
+
+        let mut c = a.clone();
+        c &= &b;
+        assert_eq!(c, &a & &b);
+        assert_eq!(
+            (ArrayND::new(vec![0b1100u8, 0b1010]) & 0b1000).to_vec(),
+            vec![8, 8]
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! Configuration of multi-threaded execution
+//!
+//! Element-wise operations, reductions and constructors like `random` split
+//! their work across threads once the Array has more elements than the
+//! threshold, running on rayon's global thread pool or on a custom one.
+//!
+//! The configuration is global, set with [`set_parallelism`], and can be
+//! overridden for the operations started inside a closure with
+//! [`with_parallelism`]. The override belongs to the thread running the
+//! closure: operations started on other threads, like the worker threads of a
+//! rayon parallel iterator, use the global threshold and stay on the pool they
+//! run on. The split points of reductions do not depend on the
+//! configuration, so changing it never changes a result.
+//!
+//! On wasm everything runs sequentially and the configuration is ignored.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::{simd, summation, NumrsError, Result};
+
+/// Default number of elements above which work is split across threads
+const DEFAULT_THRESHOLD: usize = 1_000_000;
+
+static GLOBAL: Global = Global::new();
+
+thread_local! {
+    /// Configuration set by `with_parallelism` on this thread, overriding the global one
+    static LOCAL: RefCell<Option<Parallelism>> = const { RefCell::new(None) };
+}
+
+/// Threshold and thread pool used for multi-threaded work
+///
+/// # Example
+/// ```
+/// use numrs::{with_parallelism, ArrayND, Parallelism};
+/// let array: ArrayND = ArrayND::random(10_000);
+/// let config = Parallelism::new().with_threshold(1_000).with_threads(2).unwrap();
+/// let sum = with_parallelism(&config, || array.sum());
+/// assert_eq!(sum, array.sum());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Parallelism {
+    threshold: usize,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl Default for Parallelism {
+    fn default() -> Parallelism {
+        Parallelism::new()
+    }
+}
+
+impl Parallelism {
+    /// Default configuration: a threshold of 1,000,000 elements on rayon's global pool
+    pub fn new() -> Parallelism {
+        Parallelism {
+            threshold: DEFAULT_THRESHOLD,
+            pool: None,
+        }
+    }
+
+    /// Number of elements above which work is split across threads
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Number of threads work is split across
+    pub fn num_threads(&self) -> usize {
+        match &self.pool {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
+
+    /// Splits work across threads once an Array has more than `threshold` elements
+    ///
+    /// `usize::MAX` keeps everything on the calling thread, 0 always splits.
+    pub fn with_threshold(self, threshold: usize) -> Parallelism {
+        Parallelism { threshold, ..self }
+    }
+
+    /// Runs multi-threaded work on a new thread pool with `threads` threads,
+    /// failing if the pool cannot be created
+    pub fn with_threads(self, threads: usize) -> Result<Parallelism> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|error| NumrsError::ThreadPool(error.to_string()))?;
+        Ok(self.with_pool(Arc::new(pool)))
+    }
+
+    /// Runs multi-threaded work on `pool` instead of rayon's global pool
+    pub fn with_pool(self, pool: Arc<ThreadPool>) -> Parallelism {
+        Parallelism {
+            pool: Some(pool),
+            ..self
+        }
+    }
+
+    /// Measures the Array size from which splitting work across threads pays
+    /// off on this machine, and returns the configuration with that threshold
+    ///
+    /// Times sequential and multi-threaded sums and element-wise updates on
+    /// this configuration's pool for growing sizes, which takes around a second.
+    /// On wasm, where everything runs sequentially, the threshold is `usize::MAX`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use numrs::{set_parallelism, Parallelism};
+    /// set_parallelism(Parallelism::new().auto_tune());
+    /// ```
+    pub fn auto_tune(self) -> Parallelism {
+        if cfg!(target_family = "wasm") {
+            return self.with_threshold(usize::MAX);
+        }
+        let threshold = (10..=24)
+            .map(|exponent| 1usize << exponent)
+            .find(|&size| self.speedup(size) > 1.0)
+            .unwrap_or(usize::MAX);
+        self.with_threshold(threshold)
+    }
+
+    /// How many times faster the multi-threaded kernels are than the
+    /// sequential ones on `size` elements
+    fn speedup(&self, size: usize) -> f64 {
+        let mut data = vec![1.0f64; size];
+        let mut kernels = |parallel: bool| {
+            let sum = summation::blocked_sum(&data, parallel, simd::sum);
+            crate::ops::map_in_place(&mut data, parallel, None, move |x| x * 0.5 + sum * 1e-12);
+        };
+        let sequential = fastest_of(5, || kernels(false));
+        let parallel = fastest_of(5, || self.install(|| kernels(true)));
+        sequential.as_secs_f64() / parallel.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Runs `f` on this configuration's pool, or on the calling thread if it
+    /// uses rayon's global pool
+    fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+}
+
+/// Shortest of `runs` timings of `f`
+fn fastest_of(runs: usize, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Configuration used outside of `with_parallelism`
+struct Global {
+    threshold: AtomicUsize,
+    pool: RwLock<Option<Arc<ThreadPool>>>,
+}
+
+impl Global {
+    const fn new() -> Global {
+        Global {
+            threshold: AtomicUsize::new(DEFAULT_THRESHOLD),
+            pool: RwLock::new(None),
+        }
+    }
+
+    fn set(&self, config: Parallelism) {
+        self.threshold.store(config.threshold, Ordering::Relaxed);
+        *self.pool.write().unwrap_or_else(|error| error.into_inner()) = config.pool;
+    }
+
+    /// The configuration, keeping work started on a rayon worker thread on its pool
+    fn get(&self) -> Parallelism {
+        let pool = match rayon::current_thread_index() {
+            Some(_) => None,
+            None => self
+                .pool
+                .read()
+                .unwrap_or_else(|error| error.into_inner())
+                .clone(),
+        };
+        Parallelism {
+            threshold: self.threshold.load(Ordering::Relaxed),
+            pool,
+        }
+    }
+}
+
+/// Replaces the global configuration used outside of [`with_parallelism`]
+pub fn set_parallelism(config: Parallelism) {
+    GLOBAL.set(config);
+}
+
+/// Configuration used by operations started on the calling thread
+///
+/// On a rayon worker thread without its own override this is the global
+/// threshold on the worker's pool.
+pub fn parallelism() -> Parallelism {
+    LOCAL
+        .with(|local| local.borrow().clone())
+        .unwrap_or_else(|| GLOBAL.get())
+}
+
+/// Runs `f` with `config` instead of the global configuration
+///
+/// Applies to the operations `f` starts on its own thread, not to operations
+/// started by other threads in the meantime, including the worker threads `f`
+/// splits work across. With a custom pool `f` itself runs on that pool.
+pub fn with_parallelism<R: Send>(config: &Parallelism, f: impl FnOnce() -> R + Send) -> R {
+    config.install(|| {
+        let _restore = Restore(LOCAL.with(|local| local.replace(Some(config.clone()))));
+        f()
+    })
+}
+
+/// Puts back the previous thread-local configuration, even if `f` panics
+struct Restore(Option<Parallelism>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        LOCAL.with(|local| local.replace(self.0.take()));
+    }
+}
+
+/// Runs `f` on the configured pool
+pub(crate) fn install<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    parallelism().install(f)
+}
+
+/// Runs `f` with `true` on the configured pool if `len` elements are worth
+/// splitting across threads, otherwise with `false` on the calling thread
+#[cfg(target_family = "unix")]
+pub(crate) fn run<R: Send>(len: usize, f: impl FnOnce(bool) -> R + Send) -> R {
+    let threshold = LOCAL
+        .with(|local| local.borrow().as_ref().map(|config| config.threshold))
+        .unwrap_or_else(|| GLOBAL.threshold.load(Ordering::Relaxed));
+    if len <= threshold {
+        return f(false);
+    }
+    parallelism().install(|| f(true))
+}
+
+#[cfg(target_family = "wasm")]
+pub(crate) fn run<R: Send>(_len: usize, f: impl FnOnce(bool) -> R + Send) -> R {
+    f(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parallelism, run, with_parallelism, Global, Parallelism};
+    use crate::ArrayND;
+
+    #[test]
+    fn local_overrides_global() {
+        let config = Parallelism::new().with_threshold(10);
+        with_parallelism(&config, || {
+            assert_eq!(parallelism().threshold(), 10);
+            assert!(run(11, |parallel| parallel));
+            assert!(!run(10, |parallel| parallel));
+        });
+        assert_ne!(parallelism().threshold(), 10);
+    }
+
+    #[test]
+    fn custom_pool() {
+        let config = Parallelism::new()
+            .with_threshold(0)
+            .with_threads(3)
+            .unwrap();
+        assert_eq!(config.num_threads(), 3);
+        let threads = with_parallelism(&config, || run(1, |_| rayon::current_num_threads()));
+        assert_eq!(threads, 3);
+    }
+
+    #[test]
+    fn results_do_not_depend_on_config() {
+        let array: ArrayND = ArrayND::random(50_000);
+        let expected = (array.sum(), array.min(), (&array * 2.).to_vec());
+        for threads in [1, 4] {
+            let config = Parallelism::new()
+                .with_threshold(0)
+                .with_threads(threads)
+                .unwrap();
+            let result = with_parallelism(&config, || {
+                let copy = array.clone();
+                (copy.sum(), copy.min(), (copy * 2.).to_vec())
+            });
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn parallel_random() {
+        let config = Parallelism::new().with_threshold(0);
+        let array: ArrayND<u8> = with_parallelism(&config, || ArrayND::random(100_000));
+        assert_eq!(array.shape(), &[100_000]);
+        assert!(array.min() < 10 && array.max() > 245);
+        let range: ArrayND<i64> = with_parallelism(&config, || ArrayND::random_range(1_000, -3, 3));
+        assert!(range.min() >= -3 && range.max() < 3);
+    }
+
+    #[test]
+    fn global_config() {
+        let global = Global::new();
+        assert_eq!(global.get().threshold(), 1_000_000);
+        let config = Parallelism::new()
+            .with_threshold(123)
+            .with_threads(2)
+            .unwrap();
+        global.set(config);
+        assert_eq!(global.get().threshold(), 123);
+        assert_eq!(global.get().num_threads(), 2);
+
+        // Worker threads stay on their own pool
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .unwrap();
+        assert_eq!(pool.install(|| global.get().num_threads()), 3);
+    }
+
+    #[test]
+    fn nested_work_stays_on_pool() {
+        let config = Parallelism::new().with_threads(2).unwrap();
+        let (_, threads) = with_parallelism(&config, || {
+            rayon::join(|| (), || parallelism().num_threads())
+        });
+        assert_eq!(threads, 2);
+    }
+}
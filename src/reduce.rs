@@ -0,0 +1,490 @@
+//! Reductions along a single axis
+//!
+//! Every reduction removes `axis` from the shape, or keeps it with length 1
+//! when `keepdims` is set so the result broadcasts against the input. Each
+//! lane along the axis is reduced sequentially, and like `sum` the lanes are
+//! split across threads once the Array is larger than the configured
+//! threshold, see [`Parallelism`](crate::Parallelism).
+
+#[cfg(target_family = "unix")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::ops::{Add, Mul};
+
+use crate::{
+    empty_min_max, error, fold_min_max, layout, parallel, simd, summation, ArrayND, Element,
+    NumrsError, Result,
+};
+
+/// Elements along the reduced axis for one position of the other axes
+struct Lane<'a, T> {
+    data: &'a [T],
+    offset: isize,
+    stride: isize,
+    remaining: usize,
+}
+
+impl<T: Copy> Iterator for Lane<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.data[self.offset as usize];
+        self.offset += self.stride;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Copy> ExactSizeIterator for Lane<'_, T> {}
+
+impl<T: Element + Add<Output = T>> Lane<'_, T> {
+    /// The `len` elements starting at position `start` of the lane
+    fn sub_lane(&self, start: usize, len: usize) -> Self {
+        Lane {
+            offset: self.offset + start as isize * self.stride,
+            remaining: len,
+            ..*self
+        }
+    }
+
+    /// Sum with the same fixed blocks and pairwise splits as `sum`, so a
+    /// contiguous lane sums exactly like the equivalent Array
+    fn blocked_sum(self) -> T {
+        if self.remaining <= summation::REDUCE_BLOCK {
+            return self.pairwise_sum();
+        }
+        let partials: Vec<T> = (0..self.remaining)
+            .step_by(summation::REDUCE_BLOCK)
+            .map(|start| {
+                let len = summation::REDUCE_BLOCK.min(self.remaining - start);
+                self.sub_lane(start, len).pairwise_sum()
+            })
+            .collect();
+        summation::pairwise_sum(&partials, simd::sum)
+    }
+
+    fn pairwise_sum(self) -> T {
+        if self.remaining <= summation::BLOCK {
+            let mut block = [T::zero(); summation::BLOCK];
+            let len = self.remaining;
+            block.iter_mut().zip(self).for_each(|(slot, x)| *slot = x);
+            return simd::sum(&block[..len]);
+        }
+        let half = self.remaining / 2;
+        self.sub_lane(0, half)
+            .pairwise_sum()
+            .wrapping_add(self.sub_lane(half, self.remaining - half).pairwise_sum())
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn collect_lanes<U: Send>(
+    bases: &[usize],
+    _parallel: bool,
+    f: impl Fn(usize) -> Result<U> + Sync + Send,
+) -> Result<Vec<U>> {
+    bases.iter().map(|&base| f(base)).collect()
+}
+
+#[cfg(target_family = "unix")]
+fn collect_lanes<U: Send>(
+    bases: &[usize],
+    parallel: bool,
+    f: impl Fn(usize) -> Result<U> + Sync + Send,
+) -> Result<Vec<U>> {
+    if parallel {
+        bases.par_iter().map(|&base| f(base)).collect()
+    } else {
+        bases.iter().map(|&base| f(base)).collect()
+    }
+}
+
+/// Position of the first element `better` prefers over all others, NaN winning
+/// over everything, or `None` if the lane is empty
+fn arg_best<T: Element>(lane: Lane<T>, better: impl Fn(T, T) -> bool) -> Option<i64> {
+    let mut best: Option<(usize, T)> = None;
+    for (i, x) in lane.enumerate() {
+        match best {
+            Some((_, current)) if current.is_nan() => break,
+            Some((_, current)) if !x.is_nan() && !better(x, current) => {}
+            _ => best = Some((i, x)),
+        }
+    }
+    best.map(|(i, _)| i as i64)
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Reduces every lane along `axis` with `f`, collecting the results in an
+    /// Array of the remaining axes
+    fn reduce_axis<U: Element>(
+        &self,
+        axis: usize,
+        keepdims: bool,
+        f: impl Fn(Lane<T>) -> Result<U> + Sync + Send,
+    ) -> Result<ArrayND<U>> {
+        layout::check_axis(axis, self.ndim())?;
+        let mut shape = self.shape.clone();
+        let mut strides = self.strides.clone();
+        let len = shape.remove(axis);
+        let stride = strides.remove(axis);
+
+        let bases: Vec<usize> = layout::Offsets::new(0, &shape, &strides).collect();
+        let data = parallel::run(self.size, |parallel| {
+            collect_lanes(&bases, parallel, |base| {
+                f(Lane {
+                    data: &self.data,
+                    offset: base as isize,
+                    stride,
+                    remaining: len,
+                })
+            })
+        })?;
+        if keepdims {
+            shape.insert(axis, 1);
+        }
+        ArrayND::try_from_shape_vec(data, shape)
+    }
+
+    /// Smallest element along `axis`
+    ///
+    /// NaN if a lane contains NaN. Panics if `axis` is out of bounds, or if a
+    /// non-float Array has an empty lane.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND<i32> = ArrayND::from_shape_vec(vec![3, 1, 4, 1, 5, 9], vec![2, 3]);
+    /// assert_eq!(array.min_axis(1, false).to_vec(), vec![1, 1]);
+    /// assert_eq!(array.min_axis(0, true).shape(), &[1, 3]);
+    /// ```
+    pub fn min_axis(&self, axis: usize, keepdims: bool) -> ArrayND<T> {
+        error::or_panic(self.try_min_axis(axis, keepdims))
+    }
+
+    /// Smallest element along `axis`, failing if `axis` is out of bounds or a
+    /// non-float Array has an empty lane
+    pub fn try_min_axis(&self, axis: usize, keepdims: bool) -> Result<ArrayND<T>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            let stats = lane.fold(None, |acc, x| fold_min_max(acc, x, false));
+            Ok(stats.map_or_else(empty_min_max, Ok)?.0)
+        })
+    }
+
+    /// Largest element along `axis`
+    ///
+    /// NaN if a lane contains NaN. Panics if `axis` is out of bounds, or if a
+    /// non-float Array has an empty lane.
+    pub fn max_axis(&self, axis: usize, keepdims: bool) -> ArrayND<T> {
+        error::or_panic(self.try_max_axis(axis, keepdims))
+    }
+
+    /// Largest element along `axis`, failing if `axis` is out of bounds or a
+    /// non-float Array has an empty lane
+    pub fn try_max_axis(&self, axis: usize, keepdims: bool) -> Result<ArrayND<T>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            let stats = lane.fold(None, |acc, x| fold_min_max(acc, x, false));
+            Ok(stats.map_or_else(empty_min_max, Ok)?.1)
+        })
+    }
+
+    /// Position of the smallest element along `axis`, the first one on ties
+    ///
+    /// The position of the first NaN if a lane contains NaN. Panics if `axis`
+    /// is out of bounds or a lane is empty.
+    ///
+    /// With `keepdims` the result can be passed to [`gather`](ArrayND::gather)
+    /// to pick out the minimums.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![3., 1., 4., 1., 5., 9.], vec![2, 3]);
+    /// assert_eq!(array.argmin(1, false).to_vec(), vec![1, 0]);
+    /// assert_eq!(array.argmax(0, false).to_vec(), vec![0, 1, 1]);
+    /// ```
+    pub fn argmin(&self, axis: usize, keepdims: bool) -> ArrayND<i64> {
+        error::or_panic(self.try_argmin(axis, keepdims))
+    }
+
+    /// Position of the smallest element along `axis`, failing if `axis` is out
+    /// of bounds or a lane is empty
+    pub fn try_argmin(&self, axis: usize, keepdims: bool) -> Result<ArrayND<i64>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            arg_best(lane, |x, best| x < best).ok_or_else(|| {
+                NumrsError::EmptyReduction("attempt to get argmin of an empty lane".to_string())
+            })
+        })
+    }
+
+    /// Position of the largest element along `axis`, the first one on ties
+    ///
+    /// The position of the first NaN if a lane contains NaN. Panics if `axis`
+    /// is out of bounds or a lane is empty.
+    pub fn argmax(&self, axis: usize, keepdims: bool) -> ArrayND<i64> {
+        error::or_panic(self.try_argmax(axis, keepdims))
+    }
+
+    /// Position of the largest element along `axis`, failing if `axis` is out
+    /// of bounds or a lane is empty
+    pub fn try_argmax(&self, axis: usize, keepdims: bool) -> Result<ArrayND<i64>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            arg_best(lane, |x, best| x > best).ok_or_else(|| {
+                NumrsError::EmptyReduction("attempt to get argmax of an empty lane".to_string())
+            })
+        })
+    }
+
+    /// Whether any element along `axis` is non-zero, `false` for empty lanes
+    ///
+    /// Panics if `axis` is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let mask = ArrayND::from_shape_vec(vec![true, false, false, false], vec![2, 2]);
+    /// assert_eq!(mask.any(1, false).to_vec(), vec![true, false]);
+    /// assert_eq!(mask.all(0, false).to_vec(), vec![false, false]);
+    /// ```
+    pub fn any(&self, axis: usize, keepdims: bool) -> ArrayND<bool> {
+        error::or_panic(self.try_any(axis, keepdims))
+    }
+
+    /// Whether any element along `axis` is non-zero, failing if `axis` is out of bounds
+    pub fn try_any(&self, axis: usize, keepdims: bool) -> Result<ArrayND<bool>> {
+        self.reduce_axis(axis, keepdims, |mut lane| Ok(lane.any(|x| x != T::zero())))
+    }
+
+    /// Whether every element along `axis` is non-zero, `true` for empty lanes
+    ///
+    /// Panics if `axis` is out of bounds.
+    pub fn all(&self, axis: usize, keepdims: bool) -> ArrayND<bool> {
+        error::or_panic(self.try_all(axis, keepdims))
+    }
+
+    /// Whether every element along `axis` is non-zero, failing if `axis` is out of bounds
+    pub fn try_all(&self, axis: usize, keepdims: bool) -> Result<ArrayND<bool>> {
+        self.reduce_axis(axis, keepdims, |mut lane| Ok(lane.all(|x| x != T::zero())))
+    }
+}
+
+impl<T: Element + Add<Output = T>> ArrayND<T> {
+    /// Sums the elements along `axis` with pairwise summation
+    ///
+    /// Integer sums wrap around on overflow like [`sum`](ArrayND::sum). Panics
+    /// if `axis` is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![2, 3]);
+    /// assert_eq!(array.sum_axis(0, false).to_vec(), vec![5., 7., 9.]);
+    /// assert_eq!(array.sum_axis(1, true).shape(), &[2, 1]);
+    /// ```
+    pub fn sum_axis(&self, axis: usize, keepdims: bool) -> ArrayND<T> {
+        error::or_panic(self.try_sum_axis(axis, keepdims))
+    }
+
+    /// Sums the elements along `axis`, failing if `axis` is out of bounds
+    pub fn try_sum_axis(&self, axis: usize, keepdims: bool) -> Result<ArrayND<T>> {
+        self.reduce_axis(axis, keepdims, |lane| Ok(lane.blocked_sum()))
+    }
+
+    /// Arithmetic mean along `axis`, NaN for empty lanes
+    ///
+    /// Like [`mean`](ArrayND::mean), float elements are summed in their own
+    /// type before dividing and integers in a wider type, so the sum cannot
+    /// overflow. Panics if `axis` is out of bounds.
+    pub fn mean_axis(&self, axis: usize, keepdims: bool) -> ArrayND<f64> {
+        error::or_panic(self.try_mean_axis(axis, keepdims))
+    }
+
+    /// Arithmetic mean along `axis`, failing if `axis` is out of bounds
+    pub fn try_mean_axis(&self, axis: usize, keepdims: bool) -> Result<ArrayND<f64>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            let len = lane.len();
+            let sum = if T::DTYPE.is_float() {
+                lane.blocked_sum().to_f64()
+            } else {
+                lane.map(|x| x.to_i128()).sum::<i128>() as f64
+            };
+            Ok(sum / len as f64)
+        })
+    }
+}
+
+impl<T: Element + Mul<Output = T>> ArrayND<T> {
+    /// Multiplies the elements along `axis`, 1 for empty lanes
+    ///
+    /// Integer products wrap around on overflow, like the `wrapping_mul` of
+    /// the element type. Panics if `axis` is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND<i32> = ArrayND::from_shape_vec(vec![1, 2, 3, 4, 5, 6], vec![2, 3]);
+    /// assert_eq!(array.prod_axis(1, false).to_vec(), vec![6, 120]);
+    /// ```
+    pub fn prod_axis(&self, axis: usize, keepdims: bool) -> ArrayND<T> {
+        error::or_panic(self.try_prod_axis(axis, keepdims))
+    }
+
+    /// Multiplies the elements along `axis`, failing if `axis` is out of bounds
+    pub fn try_prod_axis(&self, axis: usize, keepdims: bool) -> Result<ArrayND<T>> {
+        self.reduce_axis(axis, keepdims, |lane| {
+            Ok(lane.fold(T::one(), |prod, x| prod.wrapping_mul(x)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayND, IndexMode, NumrsError};
+
+    fn get_cube() -> ArrayND<i32> {
+        ArrayND::from_shape_vec((0..24).collect(), vec![2, 3, 4])
+    }
+
+    #[test]
+    fn sum_axis() {
+        let cube = get_cube();
+        let sum = cube.sum_axis(1, false);
+        assert_eq!(sum.shape(), &[2, 4]);
+        assert_eq!(sum.to_vec(), vec![12, 15, 18, 21, 48, 51, 54, 57]);
+        assert_eq!(cube.sum_axis(0, true).shape(), &[1, 3, 4]);
+        assert_eq!(cube.sum_axis(2, false).sum(), cube.sum());
+    }
+
+    #[test]
+    fn sum_axis_matches_sum() {
+        let array: ArrayND = ArrayND::random(200_000);
+        let total = array.sum_axis(0, false).to_vec()[0];
+        assert_eq!(total.to_bits(), array.sum().to_bits());
+    }
+
+    #[test]
+    fn reduce_transposed() {
+        let transposed = get_cube().transpose();
+        assert_eq!(transposed.shape(), &[4, 3, 2]);
+        assert_eq!(
+            transposed.sum_axis(2, false),
+            get_cube().sum_axis(0, false).transpose()
+        );
+        assert_eq!(
+            transposed.max_axis(0, false).to_vec(),
+            vec![3, 15, 7, 19, 11, 23]
+        );
+    }
+
+    #[test]
+    fn mean_and_prod_axis() {
+        let array: ArrayND = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![2, 3]);
+        assert_eq!(array.mean_axis(0, false).to_vec(), vec![2.5, 3.5, 4.5]);
+        assert_eq!(array.mean_axis(1, true).to_vec(), vec![2., 5.]);
+        assert_eq!(array.prod_axis(0, false).to_vec(), vec![4., 10., 18.]);
+    }
+
+    #[test]
+    fn small_integer_overflow() {
+        let array = ArrayND::from_shape_vec(vec![100i8, 2, 100, 3], vec![2, 2]);
+        assert_eq!(array.prod_axis(0, false).to_vec(), vec![16, 6]);
+        assert_eq!(
+            array.try_mean_axis(0, false).unwrap().to_vec(),
+            vec![100., 2.5]
+        );
+        assert_eq!(array.sum_axis(0, false).to_vec(), vec![-56, 5]);
+        let column = ArrayND::from_shape_vec(vec![100i8, 100], vec![2, 1]);
+        assert_eq!(column.sum_axis(0, false).to_vec(), vec![-56]);
+        let bytes = ArrayND::from_shape_vec(vec![255u8, 255, 16, 16], vec![2, 2]);
+        assert_eq!(bytes.prod_axis(1, false).to_vec(), vec![1, 0]);
+        assert_eq!(bytes.mean_axis(1, false).to_vec(), vec![255., 16.]);
+        assert_eq!(bytes.sum_axis(1, false).to_vec(), vec![254, 32]);
+        let long = ArrayND::from_shape_vec(vec![200u8; 3000], vec![1000, 3]);
+        assert_eq!(long.sum_axis(0, false).to_vec(), vec![64; 3]);
+    }
+
+    #[test]
+    fn min_max_axis() {
+        let array = ArrayND::from_shape_vec(vec![3., f64::NAN, 4., 1., 5., 9.], vec![2, 3]);
+        assert_eq!(array.min_axis(1, false).to_vec()[1], 1.);
+        assert!(array.min_axis(1, false).to_vec()[0].is_nan());
+        assert_eq!(array.max_axis(0, false).to_vec()[2], 9.);
+    }
+
+    #[test]
+    fn argmin_argmax() {
+        let array = ArrayND::from_shape_vec(vec![2, 7, 7, 1, 1, 0], vec![2, 3]);
+        assert_eq!(array.argmax(1, false).to_vec(), vec![1, 0]);
+        assert_eq!(array.argmin(1, false).to_vec(), vec![0, 2]);
+        assert_eq!(array.argmin(0, false).to_vec(), vec![1, 1, 1]);
+
+        let nan = ArrayND::new(vec![1., f64::NAN, 3., f64::NAN]);
+        assert_eq!(nan.argmax(0, false).to_vec(), vec![1]);
+        assert_eq!(nan.argmin(0, false).to_vec(), vec![1]);
+    }
+
+    #[test]
+    fn argmax_gathers_max() {
+        let cube = get_cube().transpose();
+        let positions = cube.argmax(1, true);
+        assert_eq!(
+            cube.gather(1, &positions, IndexMode::Raise),
+            cube.max_axis(1, true)
+        );
+    }
+
+    #[test]
+    fn any_all() {
+        let array = ArrayND::from_shape_vec(vec![0, 1, 0, 0, 2, 3], vec![2, 3]);
+        assert_eq!(array.any(0, false).to_vec(), vec![false, true, true]);
+        assert_eq!(array.all(1, false).to_vec(), vec![false, false]);
+        assert_eq!(array.all(0, true).to_vec(), vec![false, true, false]);
+
+        let empty: ArrayND<bool> = ArrayND::from_shape_vec(vec![], vec![2, 0]);
+        assert_eq!(empty.any(1, false).to_vec(), vec![false, false]);
+        assert_eq!(empty.all(1, false).to_vec(), vec![true, true]);
+    }
+
+    #[test]
+    fn empty_lanes() {
+        let empty: ArrayND<i32> = ArrayND::from_shape_vec(vec![], vec![0, 3]);
+        assert_eq!(empty.sum_axis(0, false).to_vec(), vec![0, 0, 0]);
+        assert_eq!(empty.prod_axis(0, false).to_vec(), vec![1, 1, 1]);
+        assert_eq!(empty.sum_axis(1, false).shape(), &[0]);
+        assert!(empty.mean_axis(0, false).to_vec()[0].is_nan());
+        assert!(matches!(
+            empty.try_min_axis(0, false),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+        assert!(matches!(
+            empty.try_argmax(0, false),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+
+        let floats: ArrayND = ArrayND::from_shape_vec(vec![], vec![0, 2]);
+        assert!(floats.max_axis(0, false).to_vec()[0].is_nan());
+    }
+
+    #[test]
+    fn axis_out_of_bounds() {
+        assert!(matches!(
+            get_cube().try_sum_axis(3, false),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn reduce_axis_parallel() {
+        let array = ArrayND::from_shape_vec(vec![1i64; 2_000_000], vec![1_000, 2_000]);
+        let sum = array.sum_axis(0, false);
+        assert_eq!(sum.shape(), &[2_000]);
+        assert!(sum.iter().all(|&x| x == 1_000));
+        assert_eq!(array.sum_axis(1, false).sum(), 2_000_000);
+        assert_eq!(array.argmax(1, false).max(), 0);
+    }
+}
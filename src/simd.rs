@@ -0,0 +1,944 @@
+//! Explicitly vectorised kernels
+//!
+//! Sums and dot products are accumulated in `LANES` interleaved accumulators:
+//! accumulator `j` adds up the elements at positions `j`, `j + LANES`, ...,
+//! the accumulators are combined by repeatedly adding the upper half onto the
+//! lower half, and the remaining elements are added one by one. The portable
+//! code and the vector code for every instruction set follow exactly this
+//! order, so they give bit-identical results and the choice of kernel never
+//! changes a result.
+//!
+//! `f64` and `f32` data use SSE2 or, when the CPU supports it, AVX2 on x86_64
+//! and `simd128` on wasm builds with that target feature. Other element types
+//! and targets use the portable code, which the compiler is free to vectorise.
+//!
+//! Min/max and the four arithmetic operators have vector kernels for the same
+//! types. The arithmetic kernels round exactly like the scalar operators.
+
+use std::{
+    any::TypeId,
+    ops::{Add, Mul},
+    slice,
+};
+
+use crate::Element;
+
+/// Number of interleaved accumulators of the sum and dot kernels
+pub(crate) const LANES: usize = 16;
+
+/// Largest number of vector registers holding `LANES` accumulators
+const MAX_REGISTERS: usize = LANES / 2;
+
+/// Entry points for one vector type, compiled with the given target features
+macro_rules! kernels {
+    ($(#[$attr:meta])* $lanes:ty, $float:ty => $sum:ident, $dot:ident, $min_max:ident, $binary:ident) => {
+        $(#[$attr])*
+        pub(in crate::simd) unsafe fn $sum(data: &[$float]) -> $float {
+            crate::simd::lanes::sum_lanes::<$lanes>(data)
+        }
+
+        $(#[$attr])*
+        pub(in crate::simd) unsafe fn $dot(lhs: &[$float], rhs: &[$float]) -> $float {
+            crate::simd::lanes::dot_lanes::<$lanes>(lhs, rhs)
+        }
+
+        $(#[$attr])*
+        pub(in crate::simd) unsafe fn $min_max(data: &[$float]) -> Option<($float, $float)> {
+            crate::simd::lanes::min_max_lanes::<$lanes>(data)
+        }
+
+        $(#[$attr])*
+        pub(in crate::simd) unsafe fn $binary(
+            arith: crate::simd::Arith,
+            out: *mut $float,
+            lhs: *const $float,
+            rhs: crate::simd::Source<$float>,
+            len: usize,
+        ) {
+            crate::simd::lanes::binary_lanes::<$lanes>(arith, out, lhs, rhs, len)
+        }
+    };
+}
+
+/// Calls the entry point `$kernel` for the best instruction set of the CPU,
+/// `None` if there is no vector code for the target
+macro_rules! dispatch {
+    ($kernel:ident($($arg:expr),*)) => {{
+        #[cfg(target_arch = "x86_64")]
+        let result = Some(if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU supports AVX2
+            unsafe { x86::avx2::$kernel($($arg),*) }
+        } else {
+            // SAFETY: SSE2 is part of x86_64
+            unsafe { x86::sse2::$kernel($($arg),*) }
+        });
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        // SAFETY: the module is compiled with simd128
+        let result = Some(unsafe { wasm::simd128::$kernel($($arg),*) });
+        #[cfg(not(any(
+            target_arch = "x86_64",
+            all(target_arch = "wasm32", target_feature = "simd128")
+        )))]
+        let result = {
+            let _ = ($($arg),*);
+            None
+        };
+        result
+    }};
+}
+
+/// Arithmetic operator with a vector kernel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// `y - x`
+    RevSub,
+    /// `y / x`
+    RevDiv,
+}
+
+impl Arith {
+    /// The operator with its operands swapped
+    pub(crate) fn swapped(self) -> Arith {
+        match self {
+            Arith::Sub => Arith::RevSub,
+            Arith::Div => Arith::RevDiv,
+            Arith::RevSub => Arith::Sub,
+            Arith::RevDiv => Arith::Div,
+            commutative => commutative,
+        }
+    }
+}
+
+/// Sum of the elements in the order described in the module docs
+pub(crate) fn sum<T: Element + Add<Output = T>>(data: &[T]) -> T {
+    if let Some(data) = cast::<T, f64>(data) {
+        if let Some(sum) = dispatch!(sum_f64(data)) {
+            return T::from_f64(sum);
+        }
+    }
+    if let Some(data) = cast::<T, f32>(data) {
+        if let Some(sum) = dispatch!(sum_f32(data)) {
+            return T::from_f64(sum.to_f64());
+        }
+    }
+    sum_map(data, |x| x)
+}
+
+/// Sum of `value` applied to every element, in the same order as [`sum`]
+pub(crate) fn sum_map<T: Element + Add<Output = T>>(data: &[T], value: impl Fn(T) -> T) -> T {
+    let mut lanes = [T::zero(); LANES];
+    let chunks = data.chunks_exact(LANES);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        for (lane, &x) in lanes.iter_mut().zip(chunk) {
+            *lane = lane.wrapping_add(value(x));
+        }
+    }
+    tail.iter()
+        .fold(combine(lanes), |sum, &x| sum.wrapping_add(value(x)))
+}
+
+/// Sum of the products of matching elements, in the same order as [`sum`]
+pub(crate) fn dot<T: Element + Add<Output = T> + Mul<Output = T>>(lhs: &[T], rhs: &[T]) -> T {
+    let len = lhs.len().min(rhs.len());
+    let (lhs, rhs) = (&lhs[..len], &rhs[..len]);
+    if let (Some(lhs), Some(rhs)) = (cast::<T, f64>(lhs), cast::<T, f64>(rhs)) {
+        if let Some(dot) = dispatch!(dot_f64(lhs, rhs)) {
+            return T::from_f64(dot);
+        }
+    }
+    if let (Some(lhs), Some(rhs)) = (cast::<T, f32>(lhs), cast::<T, f32>(rhs)) {
+        if let Some(dot) = dispatch!(dot_f32(lhs, rhs)) {
+            return T::from_f64(dot.to_f64());
+        }
+    }
+    portable_dot(lhs, rhs)
+}
+
+/// Dot product of slices of the same length without vector code, in the same
+/// order as the vector kernels
+fn portable_dot<T: Element + Add<Output = T> + Mul<Output = T>>(lhs: &[T], rhs: &[T]) -> T {
+    let mut lanes = [T::zero(); LANES];
+    let chunks = lhs.chunks_exact(LANES).zip(rhs.chunks_exact(LANES));
+    for (lhs, rhs) in chunks {
+        for ((lane, &x), &y) in lanes.iter_mut().zip(lhs).zip(rhs) {
+            *lane = lane.wrapping_add(x.wrapping_mul(y));
+        }
+    }
+    let tail = lhs.len() - lhs.len() % LANES;
+    finish_dot(lanes, &lhs[tail..], &rhs[tail..])
+}
+
+/// (min, max) of float data with a vector kernel
+///
+/// `None` if there is no vector kernel for the type, the data is shorter than
+/// `LANES` or contains a NaN, in which case the caller falls back to a scalar loop.
+pub(crate) fn min_max<T: Element>(data: &[T]) -> Option<(T, T)> {
+    let cast_back = |(min, max): (f64, f64)| (T::from_f64(min), T::from_f64(max));
+    if let Some(data) = cast::<T, f64>(data) {
+        return dispatch!(min_max_f64(data)).flatten().map(cast_back);
+    }
+    if let Some(data) = cast::<T, f32>(data) {
+        return dispatch!(min_max_f32(data))
+            .flatten()
+            .map(|(min, max)| cast_back((min.to_f64(), max.to_f64())));
+    }
+    None
+}
+
+/// Sets `lhs[i] = lhs[i] op rhs[i]`, returning false without touching `lhs`
+/// if there is no vector kernel for the type
+pub(crate) fn zip_assign<T: Element>(arith: Arith, lhs: &mut [T], rhs: &[T]) -> bool {
+    let len = lhs.len().min(rhs.len());
+    let out = lhs.as_mut_ptr();
+    // SAFETY: `out` and `rhs` are valid for `len` elements, `out` aliasing the
+    // left operand is allowed by the kernels
+    unsafe { binary(arith, out, out, Source::Slice(rhs.as_ptr()), len) }
+}
+
+/// Sets `out[i] = lhs[i] op rhs[i]`, returning false without touching `out`
+/// if there is no vector kernel for the types
+pub(crate) fn zip<T: Element, U: Element>(
+    arith: Arith,
+    lhs: &[T],
+    rhs: &[T],
+    out: &mut [U],
+) -> bool {
+    let len = out.len().min(lhs.len()).min(rhs.len());
+    if TypeId::of::<T>() != TypeId::of::<U>() {
+        return false;
+    }
+    // SAFETY: all three are valid for `len` elements and `T` is `U`
+    unsafe {
+        binary(
+            arith,
+            out.as_mut_ptr().cast(),
+            lhs.as_ptr(),
+            Source::Slice(rhs.as_ptr()),
+            len,
+        )
+    }
+}
+
+/// Sets `data[i] = data[i] op scalar`, returning false without touching
+/// `data` if there is no vector kernel for the type
+pub(crate) fn map_assign<T: Element>(arith: Arith, data: &mut [T], scalar: T) -> bool {
+    let out = data.as_mut_ptr();
+    // SAFETY: `out` is valid for `data.len()` elements
+    unsafe { binary(arith, out, out, Source::Scalar(scalar), data.len()) }
+}
+
+/// Sets `out[i] = data[i] op scalar`, returning false without touching `out`
+/// if there is no vector kernel for the type
+pub(crate) fn map<T: Element>(arith: Arith, data: &[T], scalar: T, out: &mut [T]) -> bool {
+    let len = data.len().min(out.len());
+    // SAFETY: both are valid for `len` elements
+    unsafe {
+        binary(
+            arith,
+            out.as_mut_ptr(),
+            data.as_ptr(),
+            Source::Scalar(scalar),
+            len,
+        )
+    }
+}
+
+/// Right operand of an arithmetic kernel
+#[derive(Clone, Copy)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        all(target_arch = "wasm32", target_feature = "simd128")
+    )),
+    allow(dead_code)
+)]
+enum Source<F> {
+    Slice(*const F),
+    Scalar(F),
+}
+
+/// Sets `out[i] = lhs[i] op rhs[i]` for `len` elements with a vector kernel,
+/// returning false if there is none for the type
+///
+/// # Safety
+/// `out`, `lhs` and a slice `rhs` must be valid for `len` elements. `out` may
+/// be the same pointer as `lhs` or `rhs`.
+unsafe fn binary<T: Element>(
+    arith: Arith,
+    out: *mut T,
+    lhs: *const T,
+    rhs: Source<T>,
+    len: usize,
+) -> bool {
+    if TypeId::of::<T>() == TypeId::of::<f64>() {
+        let rhs = match rhs {
+            Source::Slice(rhs) => Source::Slice(rhs.cast()),
+            Source::Scalar(rhs) => Source::Scalar(rhs.to_f64()),
+        };
+        let (out, lhs) = (out.cast(), lhs.cast());
+        return dispatch!(binary_f64(arith, out, lhs, rhs, len)).is_some();
+    }
+    if TypeId::of::<T>() == TypeId::of::<f32>() {
+        let rhs = match rhs {
+            Source::Slice(rhs) => Source::Slice(rhs.cast()),
+            Source::Scalar(rhs) => Source::Scalar(rhs.to_f64() as f32),
+        };
+        let (out, lhs) = (out.cast(), lhs.cast());
+        return dispatch!(binary_f32(arith, out, lhs, rhs, len)).is_some();
+    }
+    false
+}
+
+/// `data` as a slice of `F` if `T` is `F`
+fn cast<T: 'static, F: 'static>(data: &[T]) -> Option<&[F]> {
+    // SAFETY: `T` and `F` are the same type
+    (TypeId::of::<T>() == TypeId::of::<F>())
+        .then(|| unsafe { slice::from_raw_parts(data.as_ptr().cast(), data.len()) })
+}
+
+/// Adds up the accumulators by repeatedly adding the upper half onto the lower half
+fn combine<T: Element + Add<Output = T>>(mut lanes: [T; LANES]) -> T {
+    let mut width = LANES;
+    while width > 1 {
+        width /= 2;
+        for i in 0..width {
+            lanes[i] = lanes[i].wrapping_add(lanes[i + width]);
+        }
+    }
+    lanes[0]
+}
+
+/// Combines the accumulators of a dot product and adds the remaining products
+fn finish_dot<T: Element + Add<Output = T> + Mul<Output = T>>(
+    lanes: [T; LANES],
+    lhs: &[T],
+    rhs: &[T],
+) -> T {
+    lhs.iter().zip(rhs).fold(combine(lanes), |sum, (&x, &y)| {
+        sum.wrapping_add(x.wrapping_mul(y))
+    })
+}
+
+/// Kernels written once for any vector type, instantiated per instruction set
+#[cfg(any(
+    target_arch = "x86_64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+))]
+mod lanes {
+    use std::ops::{Add, Div, Mul, Sub};
+
+    use super::{combine, finish_dot, Arith, Source, LANES, MAX_REGISTERS};
+    use crate::Element;
+
+    /// Vector of `WIDTH` floats on one instruction set
+    ///
+    /// The methods are only safe to call on a CPU supporting the instruction set.
+    pub(super) trait Lanes: Copy {
+        type Float: Element
+            + Add<Output = Self::Float>
+            + Sub<Output = Self::Float>
+            + Mul<Output = Self::Float>
+            + Div<Output = Self::Float>;
+
+        const WIDTH: usize;
+
+        unsafe fn splat(x: Self::Float) -> Self;
+        unsafe fn load(ptr: *const Self::Float) -> Self;
+        unsafe fn store(self, ptr: *mut Self::Float);
+        unsafe fn add(self, rhs: Self) -> Self;
+        unsafe fn sub(self, rhs: Self) -> Self;
+        unsafe fn mul(self, rhs: Self) -> Self;
+        unsafe fn div(self, rhs: Self) -> Self;
+        unsafe fn min(self, rhs: Self) -> Self;
+        unsafe fn max(self, rhs: Self) -> Self;
+        /// Whether any lane of `self` is NaN, accumulated into `mask`
+        unsafe fn nan_mask(self, mask: Self) -> Self;
+        /// Whether any lane of a NaN mask is set
+        unsafe fn any(self) -> bool;
+    }
+
+    /// Stores the accumulator registers into one array of `LANES` accumulators
+    #[inline(always)]
+    pub(super) unsafe fn spill<V: Lanes>(registers: &[V; MAX_REGISTERS]) -> [V::Float; LANES] {
+        let mut lanes = [V::Float::zero(); LANES];
+        for (r, register) in registers.iter().take(LANES / V::WIDTH).enumerate() {
+            register.store(lanes.as_mut_ptr().add(r * V::WIDTH));
+        }
+        lanes
+    }
+
+    #[inline(always)]
+    pub(super) unsafe fn sum_lanes<V: Lanes>(data: &[V::Float]) -> V::Float {
+        let mut registers = [V::splat(V::Float::zero()); MAX_REGISTERS];
+        let chunks = data.chunks_exact(LANES);
+        let tail = chunks.remainder();
+        for chunk in chunks {
+            for (r, register) in registers.iter_mut().take(LANES / V::WIDTH).enumerate() {
+                *register = register.add(V::load(chunk.as_ptr().add(r * V::WIDTH)));
+            }
+        }
+        tail.iter()
+            .fold(combine(spill(&registers)), |sum, &x| sum + x)
+    }
+
+    #[inline(always)]
+    pub(super) unsafe fn dot_lanes<V: Lanes>(lhs: &[V::Float], rhs: &[V::Float]) -> V::Float {
+        let len = lhs.len().min(rhs.len());
+        let mut registers = [V::splat(V::Float::zero()); MAX_REGISTERS];
+        for start in (0..len - len % LANES).step_by(LANES) {
+            for (r, register) in registers.iter_mut().take(LANES / V::WIDTH).enumerate() {
+                let offset = start + r * V::WIDTH;
+                let product =
+                    V::load(lhs.as_ptr().add(offset)).mul(V::load(rhs.as_ptr().add(offset)));
+                *register = register.add(product);
+            }
+        }
+        let tail = len - len % LANES;
+        finish_dot(spill(&registers), &lhs[tail..len], &rhs[tail..len])
+    }
+
+    #[inline(always)]
+    pub(super) unsafe fn min_max_lanes<V: Lanes>(
+        data: &[V::Float],
+    ) -> Option<(V::Float, V::Float)> {
+        if data.len() < LANES {
+            return None;
+        }
+        let registers = LANES / V::WIDTH;
+        let mut mins = [V::splat(data[0]); MAX_REGISTERS];
+        let mut maxs = mins;
+        let mut nan = V::splat(V::Float::zero()).nan_mask(V::splat(V::Float::zero()));
+        let chunks = data.chunks_exact(LANES);
+        let tail = chunks.remainder();
+        for chunk in chunks {
+            for r in 0..registers {
+                let x = V::load(chunk.as_ptr().add(r * V::WIDTH));
+                nan = x.nan_mask(nan);
+                mins[r] = mins[r].min(x);
+                maxs[r] = maxs[r].max(x);
+            }
+        }
+        if nan.any() || tail.iter().any(|x| x.is_nan()) {
+            return None;
+        }
+        let (mins, maxs) = (spill(&mins), spill(&maxs));
+        let (min, max) = mins
+            .iter()
+            .chain(tail)
+            .fold((data[0], data[0]), |(min, max), &x| {
+                (if x < min { x } else { min }, max)
+            });
+        let max = maxs
+            .iter()
+            .chain(tail)
+            .fold(max, |max, &x| if x > max { x } else { max });
+        Some((min, max))
+    }
+
+    #[inline(always)]
+    pub(super) unsafe fn binary_lanes<V: Lanes>(
+        arith: Arith,
+        out: *mut V::Float,
+        lhs: *const V::Float,
+        rhs: Source<V::Float>,
+        len: usize,
+    ) {
+        match arith {
+            Arith::Add => binary_op::<V, false>(out, lhs, rhs, len, V::add, |x, y| x + y),
+            Arith::Sub => binary_op::<V, false>(out, lhs, rhs, len, V::sub, |x, y| x - y),
+            Arith::Mul => binary_op::<V, false>(out, lhs, rhs, len, V::mul, |x, y| x * y),
+            Arith::Div => binary_op::<V, false>(out, lhs, rhs, len, V::div, |x, y| x / y),
+            Arith::RevSub => binary_op::<V, true>(out, lhs, rhs, len, V::sub, |x, y| x - y),
+            Arith::RevDiv => binary_op::<V, true>(out, lhs, rhs, len, V::div, |x, y| x / y),
+        }
+    }
+
+    /// Sets `out[i] = lhs[i] op rhs[i]`, or `rhs[i] op lhs[i]` if `REVERSED`
+    #[inline(always)]
+    pub(super) unsafe fn binary_op<V: Lanes, const REVERSED: bool>(
+        out: *mut V::Float,
+        lhs: *const V::Float,
+        rhs: Source<V::Float>,
+        len: usize,
+        vector: unsafe fn(V, V) -> V,
+        scalar: fn(V::Float, V::Float) -> V::Float,
+    ) {
+        let body = len - len % V::WIDTH;
+        match rhs {
+            Source::Slice(rhs) => {
+                for i in (0..body).step_by(V::WIDTH) {
+                    let (x, y) = operands::<_, REVERSED>(V::load(lhs.add(i)), V::load(rhs.add(i)));
+                    vector(x, y).store(out.add(i));
+                }
+                for i in body..len {
+                    let (x, y) = operands::<_, REVERSED>(*lhs.add(i), *rhs.add(i));
+                    *out.add(i) = scalar(x, y);
+                }
+            }
+            Source::Scalar(rhs) => {
+                let splat = V::splat(rhs);
+                for i in (0..body).step_by(V::WIDTH) {
+                    let (x, y) = operands::<_, REVERSED>(V::load(lhs.add(i)), splat);
+                    vector(x, y).store(out.add(i));
+                }
+                for i in body..len {
+                    let (x, y) = operands::<_, REVERSED>(*lhs.add(i), rhs);
+                    *out.add(i) = scalar(x, y);
+                }
+            }
+        }
+    }
+
+    /// The operands in the order they are passed to the operator
+    #[inline(always)]
+    fn operands<A, const REVERSED: bool>(x: A, y: A) -> (A, A) {
+        if REVERSED {
+            (y, x)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::lanes::Lanes;
+
+    /// Implements `Lanes` for a wrapper of an x86 vector type from its intrinsics
+    macro_rules! impl_lanes {
+        (
+            $name:ident($vector:ty), $float:ty, $width:expr,
+            $set1:ident, $loadu:ident, $storeu:ident,
+            $add:ident, $sub:ident, $mul:ident, $div:ident, $min:ident, $max:ident,
+            $unord:expr, $or:ident, $movemask:ident
+        ) => {
+            #[derive(Clone, Copy)]
+            pub(super) struct $name($vector);
+
+            impl Lanes for $name {
+                type Float = $float;
+
+                const WIDTH: usize = $width;
+
+                #[inline(always)]
+                unsafe fn splat(x: $float) -> Self {
+                    $name($set1(x))
+                }
+
+                #[inline(always)]
+                unsafe fn load(ptr: *const $float) -> Self {
+                    $name($loadu(ptr))
+                }
+
+                #[inline(always)]
+                unsafe fn store(self, ptr: *mut $float) {
+                    $storeu(ptr, self.0)
+                }
+
+                #[inline(always)]
+                unsafe fn add(self, rhs: Self) -> Self {
+                    $name($add(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn sub(self, rhs: Self) -> Self {
+                    $name($sub(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn mul(self, rhs: Self) -> Self {
+                    $name($mul(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn div(self, rhs: Self) -> Self {
+                    $name($div(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn min(self, rhs: Self) -> Self {
+                    $name($min(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn max(self, rhs: Self) -> Self {
+                    $name($max(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn nan_mask(self, mask: Self) -> Self {
+                    $name($or(mask.0, $unord(self.0, self.0)))
+                }
+
+                #[inline(always)]
+                unsafe fn any(self) -> bool {
+                    $movemask(self.0) != 0
+                }
+            }
+        };
+    }
+
+    impl_lanes!(
+        Sse2F64(__m128d),
+        f64,
+        2,
+        _mm_set1_pd,
+        _mm_loadu_pd,
+        _mm_storeu_pd,
+        _mm_add_pd,
+        _mm_sub_pd,
+        _mm_mul_pd,
+        _mm_div_pd,
+        _mm_min_pd,
+        _mm_max_pd,
+        _mm_cmpunord_pd,
+        _mm_or_pd,
+        _mm_movemask_pd
+    );
+    impl_lanes!(
+        Sse2F32(__m128),
+        f32,
+        4,
+        _mm_set1_ps,
+        _mm_loadu_ps,
+        _mm_storeu_ps,
+        _mm_add_ps,
+        _mm_sub_ps,
+        _mm_mul_ps,
+        _mm_div_ps,
+        _mm_min_ps,
+        _mm_max_ps,
+        _mm_cmpunord_ps,
+        _mm_or_ps,
+        _mm_movemask_ps
+    );
+    impl_lanes!(
+        Avx2F64(__m256d),
+        f64,
+        4,
+        _mm256_set1_pd,
+        _mm256_loadu_pd,
+        _mm256_storeu_pd,
+        _mm256_add_pd,
+        _mm256_sub_pd,
+        _mm256_mul_pd,
+        _mm256_div_pd,
+        _mm256_min_pd,
+        _mm256_max_pd,
+        _mm256_cmp_pd::<_CMP_UNORD_Q>,
+        _mm256_or_pd,
+        _mm256_movemask_pd
+    );
+    impl_lanes!(
+        Avx2F32(__m256),
+        f32,
+        8,
+        _mm256_set1_ps,
+        _mm256_loadu_ps,
+        _mm256_storeu_ps,
+        _mm256_add_ps,
+        _mm256_sub_ps,
+        _mm256_mul_ps,
+        _mm256_div_ps,
+        _mm256_min_ps,
+        _mm256_max_ps,
+        _mm256_cmp_ps::<_CMP_UNORD_Q>,
+        _mm256_or_ps,
+        _mm256_movemask_ps
+    );
+
+    pub(super) mod sse2 {
+        kernels!(super::Sse2F64, f64 => sum_f64, dot_f64, min_max_f64, binary_f64);
+        kernels!(super::Sse2F32, f32 => sum_f32, dot_f32, min_max_f32, binary_f32);
+    }
+
+    pub(super) mod avx2 {
+        kernels!(
+            #[target_feature(enable = "avx2")]
+            super::Avx2F64, f64 => sum_f64, dot_f64, min_max_f64, binary_f64
+        );
+        kernels!(
+            #[target_feature(enable = "avx2")]
+            super::Avx2F32, f32 => sum_f32, dot_f32, min_max_f32, binary_f32
+        );
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm {
+    use std::arch::wasm32::*;
+
+    use super::lanes::Lanes;
+
+    /// Implements `Lanes` for a wrapper of `v128` from the intrinsics of one lane type
+    macro_rules! impl_lanes {
+        (
+            $name:ident, $float:ty, $width:expr,
+            $splat:ident, $add:ident, $sub:ident, $mul:ident, $div:ident,
+            $min:ident, $max:ident, $ne:ident
+        ) => {
+            #[derive(Clone, Copy)]
+            pub(super) struct $name(v128);
+
+            impl Lanes for $name {
+                type Float = $float;
+
+                const WIDTH: usize = $width;
+
+                #[inline(always)]
+                unsafe fn splat(x: $float) -> Self {
+                    $name($splat(x))
+                }
+
+                #[inline(always)]
+                unsafe fn load(ptr: *const $float) -> Self {
+                    $name(v128_load(ptr.cast()))
+                }
+
+                #[inline(always)]
+                unsafe fn store(self, ptr: *mut $float) {
+                    v128_store(ptr.cast(), self.0)
+                }
+
+                #[inline(always)]
+                unsafe fn add(self, rhs: Self) -> Self {
+                    $name($add(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn sub(self, rhs: Self) -> Self {
+                    $name($sub(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn mul(self, rhs: Self) -> Self {
+                    $name($mul(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn div(self, rhs: Self) -> Self {
+                    $name($div(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn min(self, rhs: Self) -> Self {
+                    $name($min(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn max(self, rhs: Self) -> Self {
+                    $name($max(self.0, rhs.0))
+                }
+
+                #[inline(always)]
+                unsafe fn nan_mask(self, mask: Self) -> Self {
+                    $name(v128_or(mask.0, $ne(self.0, self.0)))
+                }
+
+                #[inline(always)]
+                unsafe fn any(self) -> bool {
+                    v128_any_true(self.0)
+                }
+            }
+        };
+    }
+
+    impl_lanes!(
+        Simd128F64,
+        f64,
+        2,
+        f64x2_splat,
+        f64x2_add,
+        f64x2_sub,
+        f64x2_mul,
+        f64x2_div,
+        f64x2_min,
+        f64x2_max,
+        f64x2_ne
+    );
+    impl_lanes!(
+        Simd128F32,
+        f32,
+        4,
+        f32x4_splat,
+        f32x4_add,
+        f32x4_sub,
+        f32x4_mul,
+        f32x4_div,
+        f32x4_min,
+        f32x4_max,
+        f32x4_ne
+    );
+
+    pub(super) mod simd128 {
+        kernels!(super::Simd128F64, f64 => sum_f64, dot_f64, min_max_f64, binary_f64);
+        kernels!(super::Simd128F32, f32 => sum_f32, dot_f32, min_max_f32, binary_f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{portable_dot, sum_map, Arith, Source};
+    use crate::ArrayND;
+
+    const ARITHS: [Arith; 6] = [
+        Arith::Add,
+        Arith::Sub,
+        Arith::Mul,
+        Arith::Div,
+        Arith::RevSub,
+        Arith::RevDiv,
+    ];
+
+    fn scalar_op(arith: Arith, x: f64, y: f64) -> f64 {
+        match arith {
+            Arith::Add => x + y,
+            Arith::Sub => x - y,
+            Arith::Mul => x * y,
+            Arith::Div => x / y,
+            Arith::RevSub => y - x,
+            Arith::RevDiv => y / x,
+        }
+    }
+
+    /// Random data of every length up to a few vectors past `LANES`, then a long one
+    fn inputs() -> Vec<Vec<f64>> {
+        (0..70)
+            .chain([1_001])
+            .map(|len| (ArrayND::<f64>::random(len) * 1e8 - 5e7).to_vec())
+            .collect()
+    }
+
+    fn bits(data: &[f64]) -> Vec<u64> {
+        data.iter().map(|x| x.to_bits()).collect()
+    }
+
+    /// Every kernel set that runs on this CPU, by name
+    #[cfg(target_arch = "x86_64")]
+    macro_rules! for_each_isa {
+        (|$isa:ident| $body:block) => {{
+            {
+                use super::x86::sse2 as $isa;
+                $body
+            }
+            if std::is_x86_feature_detected!("avx2") {
+                use super::x86::avx2 as $isa;
+                $body
+            }
+        }};
+    }
+
+    #[test]
+    fn sum_matches_portable() {
+        for data in inputs() {
+            let expected = sum_map(&data, |x| x);
+            assert_eq!(super::sum(&data).to_bits(), expected.to_bits());
+            let single: Vec<f32> = data.iter().map(|&x| x as f32).collect();
+            assert_eq!(
+                super::sum(&single).to_bits(),
+                sum_map(&single, |x| x).to_bits()
+            );
+            #[cfg(target_arch = "x86_64")]
+            for_each_isa!(|isa| {
+                // SAFETY: only kernel sets supported by the CPU are run
+                let (sum, sum_f32) = unsafe { (isa::sum_f64(&data), isa::sum_f32(&single)) };
+                assert_eq!(sum.to_bits(), expected.to_bits());
+                assert_eq!(sum_f32.to_bits(), sum_map(&single, |x| x).to_bits());
+            });
+        }
+    }
+
+    #[test]
+    fn dot_matches_portable() {
+        for data in inputs() {
+            let other: Vec<f64> = data.iter().rev().copied().collect();
+            let expected = portable_dot(&data, &other);
+            assert_eq!(super::dot(&data, &other).to_bits(), expected.to_bits());
+            #[cfg(target_arch = "x86_64")]
+            for_each_isa!(|isa| {
+                // SAFETY: only kernel sets supported by the CPU are run
+                let dot = unsafe { isa::dot_f64(&data, &other) };
+                assert_eq!(dot.to_bits(), expected.to_bits());
+            });
+        }
+        let ints: Vec<i64> = (0..100).collect();
+        assert_eq!(super::dot(&ints, &ints), 328_350);
+    }
+
+    #[test]
+    fn min_max_matches_scalar() {
+        for data in inputs() {
+            let scalar = data.iter().fold(None, |acc: Option<(f64, f64)>, &x| {
+                Some(acc.map_or((x, x), |(min, max)| (min.min(x), max.max(x))))
+            });
+            match super::min_max(&data) {
+                Some(stats) => assert_eq!(Some(stats), scalar),
+                None => assert!(data.len() < super::LANES),
+            }
+        }
+        let mut data = inputs().pop().unwrap();
+        data[500] = f64::NAN;
+        assert_eq!(super::min_max(&data), None);
+    }
+
+    #[test]
+    fn arithmetic_matches_scalar() {
+        let special = [
+            0.,
+            -0.,
+            1.,
+            -2.5,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            1e-310,
+        ];
+        let mut inputs = inputs();
+        inputs.push(special.iter().cycle().take(64).copied().collect());
+        for lhs in inputs {
+            let rhs: Vec<f64> = lhs.iter().rev().map(|x| x * 0.75 + 1.).collect();
+            for arith in ARITHS {
+                let expected: Vec<f64> = lhs
+                    .iter()
+                    .zip(&rhs)
+                    .map(|(&x, &y)| scalar_op(arith, x, y))
+                    .collect();
+                let mut out = vec![0.; lhs.len()];
+                assert!(super::zip(arith, &lhs, &rhs, &mut out));
+                assert_eq!(bits(&out), bits(&expected), "{:?}", arith);
+                let mut assigned = lhs.clone();
+                assert!(super::zip_assign(arith, &mut assigned, &rhs));
+                assert_eq!(bits(&assigned), bits(&expected), "{:?}", arith);
+
+                let expected: Vec<f64> = lhs.iter().map(|&x| scalar_op(arith, x, 3.)).collect();
+                assert!(super::map(arith, &lhs, 3., &mut out));
+                assert_eq!(bits(&out), bits(&expected), "{:?}", arith);
+                #[cfg(target_arch = "x86_64")]
+                for_each_isa!(|isa| {
+                    let mut assigned = lhs.clone();
+                    let out = assigned.as_mut_ptr();
+                    // SAFETY: only kernel sets supported by the CPU are run, on
+                    // buffers of the given length
+                    unsafe { isa::binary_f64(arith, out, out, Source::Scalar(3.), lhs.len()) };
+                    assert_eq!(bits(&assigned), bits(&expected), "{:?}", arith);
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn no_kernel_for_integers() {
+        let mut data = vec![1i32, 2, 3];
+        assert!(!super::map_assign(Arith::Add, &mut data, 1));
+        assert_eq!(data, vec![1, 2, 3]);
+        assert!(super::min_max(&[1u8; 100]).is_none());
+        assert_eq!(super::sum(&[1u8; 100]), 100);
+    }
+}
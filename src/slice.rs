@@ -0,0 +1,432 @@
+//! NumPy-like slicing with the `s!` macro
+//!
+//! A slice is a list of [`SliceElem`], one per axis, applied to the shape and
+//! strides of an Array to produce a view over the same buffer.
+
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+use crate::{ArrayND, ArrayView, ArrayViewMut, Element, NumrsError, Result};
+
+/// One entry of a slice built with [`s!`](crate::s)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceElem {
+    /// Positions `start..end` taking every `step`th one, negative values count
+    /// from the end of the axis and a negative step walks it backwards
+    Slice {
+        start: Option<isize>,
+        end: Option<isize>,
+        step: isize,
+    },
+    /// A single position, removing the axis
+    Index(isize),
+    /// Inserts a new axis of length 1
+    NewAxis,
+    /// Stands for as many full slices as needed to cover the remaining axes
+    Ellipsis,
+}
+
+/// Marker for inserting a new axis of length 1 in [`s!`](crate::s)
+#[derive(Clone, Copy, Debug)]
+pub struct NewAxis;
+
+impl SliceElem {
+    /// Takes every `step`th position of a slice, used by `s!` for `range;step`
+    ///
+    /// Panics if the element is not a slice.
+    pub fn step_by(self, step: isize) -> SliceElem {
+        match self {
+            SliceElem::Slice { start, end, .. } => SliceElem::Slice { start, end, step },
+            _ => panic!("only ranges can have a step, not {:?}", self),
+        }
+    }
+}
+
+impl From<RangeFull> for SliceElem {
+    fn from(_: RangeFull) -> SliceElem {
+        SliceElem::Slice {
+            start: None,
+            end: None,
+            step: 1,
+        }
+    }
+}
+
+impl From<NewAxis> for SliceElem {
+    fn from(_: NewAxis) -> SliceElem {
+        SliceElem::NewAxis
+    }
+}
+
+macro_rules! impl_from_index {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for SliceElem {
+                fn from(index: $t) -> SliceElem {
+                    SliceElem::Index(index as isize)
+                }
+            }
+
+            impl From<Range<$t>> for SliceElem {
+                fn from(range: Range<$t>) -> SliceElem {
+                    SliceElem::Slice {
+                        start: Some(range.start as isize),
+                        end: Some(range.end as isize),
+                        step: 1,
+                    }
+                }
+            }
+
+            impl From<RangeInclusive<$t>> for SliceElem {
+                fn from(range: RangeInclusive<$t>) -> SliceElem {
+                    let end = *range.end() as isize;
+                    SliceElem::Slice {
+                        start: Some(*range.start() as isize),
+                        end: if end == -1 { None } else { Some(end + 1) },
+                        step: 1,
+                    }
+                }
+            }
+
+            impl From<RangeFrom<$t>> for SliceElem {
+                fn from(range: RangeFrom<$t>) -> SliceElem {
+                    SliceElem::Slice {
+                        start: Some(range.start as isize),
+                        end: None,
+                        step: 1,
+                    }
+                }
+            }
+
+            impl From<RangeTo<$t>> for SliceElem {
+                fn from(range: RangeTo<$t>) -> SliceElem {
+                    SliceElem::Slice {
+                        start: None,
+                        end: Some(range.end as isize),
+                        step: 1,
+                    }
+                }
+            }
+
+            impl From<RangeToInclusive<$t>> for SliceElem {
+                fn from(range: RangeToInclusive<$t>) -> SliceElem {
+                    let end = range.end as isize;
+                    SliceElem::Slice {
+                        start: None,
+                        end: if end == -1 { None } else { Some(end + 1) },
+                        step: 1,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_index!(isize, usize, i32);
+
+/// Builds the slice argument of [`ArrayND::slice`]
+///
+/// Each comma separated entry is one of
+/// - a range such as `1..5`, `..`, `2..` or `..=3`, optionally followed by
+///   `;step`, where the step may be negative
+/// - an index such as `2` or `-1`, which removes the axis
+/// - `NewAxis`, which inserts an axis of length 1
+/// - `...`, which expands to full slices over the axes not otherwise mentioned
+///
+/// Negative positions count from the end of the axis. Axes after the last
+/// entry are kept whole.
+///
+/// # Example
+/// ```
+/// use numrs::{s, ArrayND, NewAxis};
+/// let array: ArrayND = ArrayND::arange(0., 24., 1.).reshape(&[2, 3, 4]);
+/// let view = array.slice(s![-1, ..;2, 1..;-1]);
+/// assert_eq!(view.shape(), &[2, 2]);
+/// assert_eq!(view.to_vec(), vec![13., 12., 21., 20.]);
+/// assert_eq!(array.slice(s![..., NewAxis, 0]).shape(), &[2, 3, 1]);
+/// ```
+#[macro_export]
+macro_rules! s {
+    (@parse [$($acc:expr,)*]) => {
+        &[$($acc),*]
+    };
+    (@parse [$($acc:expr,)*] ... $(, $($rest:tt)*)?) => {
+        $crate::s!(@parse [$($acc,)* $crate::SliceElem::Ellipsis,] $($($rest)*)?)
+    };
+    (@parse [$($acc:expr,)*] $range:expr ; $step:expr $(, $($rest:tt)*)?) => {
+        $crate::s!(
+            @parse [$($acc,)* $crate::SliceElem::from($range).step_by($step as isize),]
+            $($($rest)*)?
+        )
+    };
+    (@parse [$($acc:expr,)*] $elem:expr $(, $($rest:tt)*)?) => {
+        $crate::s!(@parse [$($acc,)* $crate::SliceElem::from($elem),] $($($rest)*)?)
+    };
+    ($($t:tt)*) => {
+        $crate::s!(@parse [] $($t)*)
+    };
+}
+
+/// Start offset, shape and strides of the view selected by `info`
+pub(crate) fn slice_layout(
+    offset: usize,
+    shape: &[usize],
+    strides: &[isize],
+    info: &[SliceElem],
+) -> Result<(usize, Vec<usize>, Vec<isize>)> {
+    let consumed = info
+        .iter()
+        .filter(|elem| matches!(elem, SliceElem::Slice { .. } | SliceElem::Index(_)))
+        .count();
+    let ellipses = info
+        .iter()
+        .filter(|&&elem| elem == SliceElem::Ellipsis)
+        .count();
+    if consumed > shape.len() || ellipses > 1 {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "slice {:?} does not match array of {} dimensions",
+            info,
+            shape.len()
+        )));
+    }
+
+    let mut offset = offset as isize;
+    let mut new_shape = Vec::with_capacity(shape.len());
+    let mut new_strides = Vec::with_capacity(shape.len());
+    let mut axis = 0;
+    let full = SliceElem::from(..);
+    let trailing = shape.len() - consumed;
+    let elems = info.iter().flat_map(|&elem| match elem {
+        SliceElem::Ellipsis => vec![full; trailing],
+        elem => vec![elem],
+    });
+    let implicit = if ellipses == 0 { trailing } else { 0 };
+    let elems = elems.chain(std::iter::repeat_n(full, implicit));
+
+    for elem in elems {
+        match elem {
+            SliceElem::Slice { start, end, step } => {
+                let (start, len) = resolve_slice(shape[axis], start, end, step)?;
+                if len > 0 {
+                    offset += start * strides[axis];
+                }
+                new_shape.push(len);
+                new_strides.push(strides[axis] * step);
+                axis += 1;
+            }
+            SliceElem::Index(index) => {
+                let len = shape[axis] as isize;
+                let resolved = if index < 0 { index + len } else { index };
+                if resolved < 0 || resolved >= len {
+                    return Err(NumrsError::IndexOutOfBounds(format!(
+                        "index {} is out of bounds for axis {} of length {}",
+                        index, axis, len
+                    )));
+                }
+                offset += resolved * strides[axis];
+                axis += 1;
+            }
+            SliceElem::NewAxis => {
+                new_shape.push(1);
+                new_strides.push(0);
+            }
+            SliceElem::Ellipsis => unreachable!("ellipsis was expanded"),
+        }
+    }
+    Ok((offset as usize, new_shape, new_strides))
+}
+
+/// First position and number of positions selected along an axis of length `len`
+///
+/// Follows Python's slice rules: out of range bounds are clamped rather than
+/// rejected, and omitted bounds default to the ends of the axis in the
+/// direction of `step`.
+fn resolve_slice(
+    len: usize,
+    start: Option<isize>,
+    end: Option<isize>,
+    step: isize,
+) -> Result<(isize, usize)> {
+    if step == 0 {
+        return Err(NumrsError::IndexOutOfBounds(
+            "slice step cannot be zero".to_string(),
+        ));
+    }
+    let len = len as isize;
+    let (lower, upper) = if step > 0 { (0, len) } else { (-1, len - 1) };
+    let clamp = |bound: isize| {
+        let bound = if bound < 0 { bound + len } else { bound };
+        bound.clamp(lower, upper)
+    };
+    let (start, end) = if step > 0 {
+        (start.map_or(0, clamp), end.map_or(len, clamp))
+    } else {
+        (start.map_or(len - 1, clamp), end.map_or(-1, clamp))
+    };
+    let count = if step > 0 && end > start {
+        (end - start + step - 1) / step
+    } else if step < 0 && start > end {
+        (start - end - step - 1) / -step
+    } else {
+        0
+    };
+    Ok((start, count as usize))
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Read-only view of the positions selected by `info`, see [`s!`](crate::s)
+    ///
+    /// Panics if an index is out of bounds or `info` has more entries than the
+    /// Array has axes.
+    pub fn slice(&self, info: &[SliceElem]) -> ArrayView<'_, T> {
+        self.view().slice(info)
+    }
+
+    /// Read-only view of the positions selected by `info`, failing if an
+    /// index is out of bounds or `info` has too many entries
+    pub fn try_slice(&self, info: &[SliceElem]) -> Result<ArrayView<'_, T>> {
+        self.view().try_slice(info)
+    }
+
+    /// Mutable view of the positions selected by `info`, see [`s!`](crate::s)
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{s, ArrayND};
+    /// let mut array: ArrayND = ArrayND::arange(0., 6., 1.);
+    /// array.slice_mut(s![..;-2]).fill(0.);
+    /// assert_eq!(array.to_vec(), vec![0., 0., 2., 0., 4., 0.]);
+    /// ```
+    pub fn slice_mut(&mut self, info: &[SliceElem]) -> ArrayViewMut<'_, T> {
+        self.view_mut().slice(info)
+    }
+
+    /// Mutable view of the positions selected by `info`, failing if an index
+    /// is out of bounds or `info` has too many entries
+    pub fn try_slice_mut(&mut self, info: &[SliceElem]) -> Result<ArrayViewMut<'_, T>> {
+        self.view_mut().try_slice(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_slice;
+    use crate::{ArrayND, NewAxis, NumrsError, SliceElem};
+
+    fn get_matrix() -> ArrayND {
+        ArrayND::arange(0., 12., 1.).reshape(&[3, 4])
+    }
+
+    #[test]
+    fn macro_elems() {
+        let info: &[SliceElem] = s![1..5, ..;2, -1, NewAxis, ..., 3..;-1];
+        assert_eq!(
+            info,
+            &[
+                SliceElem::Slice {
+                    start: Some(1),
+                    end: Some(5),
+                    step: 1
+                },
+                SliceElem::Slice {
+                    start: None,
+                    end: None,
+                    step: 2
+                },
+                SliceElem::Index(-1),
+                SliceElem::NewAxis,
+                SliceElem::Ellipsis,
+                SliceElem::Slice {
+                    start: Some(3),
+                    end: None,
+                    step: -1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn python_slice_rules() {
+        assert_eq!(resolve_slice(5, None, None, 1).unwrap(), (0, 5));
+        assert_eq!(resolve_slice(5, Some(1), Some(100), 2).unwrap(), (1, 2));
+        assert_eq!(resolve_slice(5, Some(-2), None, 1).unwrap(), (3, 2));
+        assert_eq!(resolve_slice(5, None, None, -1).unwrap(), (4, 5));
+        assert_eq!(resolve_slice(5, Some(3), Some(0), -2).unwrap(), (3, 2));
+        assert_eq!(resolve_slice(5, Some(1), Some(4), -1).unwrap().1, 0);
+        assert_eq!(resolve_slice(0, None, None, -1).unwrap().1, 0);
+        assert!(resolve_slice(5, None, None, 0).is_err());
+    }
+
+    #[test]
+    fn slice_ranges() {
+        let array = get_matrix();
+
+        assert_eq!(
+            array.slice(s![1..]).to_vec(),
+            (4..12).map(f64::from).collect::<Vec<_>>()
+        );
+        assert_eq!(array.slice(s![..;2, 1..=2]).to_vec(), vec![1., 2., 9., 10.]);
+        assert_eq!(
+            array.slice(s![..;-1, ..;-3]).to_vec(),
+            vec![11., 8., 7., 4., 3., 0.]
+        );
+        assert_eq!(array.slice(s![.., -1..1]).shape(), &[3, 0]);
+    }
+
+    #[test]
+    fn slice_indices() {
+        let array = get_matrix();
+
+        assert_eq!(array.slice(s![1]).to_vec(), vec![4., 5., 6., 7.]);
+        assert_eq!(array.slice(s![-1, -2]).shape(), &[] as &[usize]);
+        assert_eq!(array.slice(s![-1, -2]).item(&[]), 10.);
+        assert_eq!(array.slice(s![.., 0]).to_vec(), vec![0., 4., 8.]);
+    }
+
+    #[test]
+    fn slice_new_axis_and_ellipsis() {
+        let array = ArrayND::arange(0., 24., 1.).reshape(&[2, 3, 4]);
+
+        assert_eq!(array.slice(s![NewAxis]).shape(), &[1, 2, 3, 4]);
+        assert_eq!(array.slice(s![..., 1]).shape(), &[2, 3]);
+        assert_eq!(
+            array.slice(s![..., 1]).to_vec(),
+            array.slice(s![.., .., 1]).to_vec()
+        );
+        assert_eq!(array.slice(s![0, ..., NewAxis]).shape(), &[3, 4, 1]);
+        assert_eq!(array.slice(s![1, ..., 2, 3]).item(&[]), 23.);
+    }
+
+    #[test]
+    fn slice_of_transposed_view() {
+        let array = get_matrix().transpose();
+        let view = array.slice(s![1..3, ..;-1]);
+
+        assert_eq!(view.to_vec(), vec![9., 5., 1., 10., 6., 2.]);
+        assert_eq!(view.slice(s![.., 1]).to_vec(), vec![5., 6.]);
+    }
+
+    #[test]
+    fn slice_mut_writes_through() {
+        let mut array = get_matrix();
+        array.slice_mut(s![1.., ..;-2]).fill(-1.);
+
+        assert_eq!(
+            array.to_vec(),
+            vec![0., 1., 2., 3., 4., -1., 6., -1., 8., -1., 10., -1.]
+        );
+        assert_eq!(array.min(), -1.);
+    }
+
+    #[test]
+    fn slice_errors() {
+        let array = get_matrix();
+
+        assert!(matches!(
+            array.try_slice(s![3]),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(array.try_slice(s![0, 0, 0]).is_err());
+        assert!(array.try_slice(s![..., 0, ...]).is_err());
+        assert!(array.try_slice(s![..;0]).is_err());
+    }
+}
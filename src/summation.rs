@@ -0,0 +1,317 @@
+//! Summation algorithms
+//!
+//! `sum` uses pairwise summation: the data is halved until blocks of at most
+//! `BLOCK` elements are left, which are added up by the vectorised kernels in
+//! `simd`. The rounding error grows with the logarithm of the length instead
+//! of linearly, at the speed of a plain loop.
+//!
+//! Large inputs are first cut into fixed blocks of `REDUCE_BLOCK` elements.
+//! Every block is summed pairwise, on any thread, and the block sums are then
+//! combined pairwise in order. Neither the blocks nor the combining order
+//! depend on how rayon splits the work, so the sequential and parallel sums,
+//! wasm and any number of threads all give bit-identical results. `dot` is
+//! computed the same way.
+//!
+//! Kahan and Neumaier compensated summation carry the rounding error of every
+//! addition along and are accurate even on adversarial inputs, but are slower
+//! and always sequential. They are opt-in through [`ArrayND::sum_with`].
+
+#[cfg(target_family = "unix")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::ops::{Add, Mul, Range, Sub};
+
+use crate::{error, parallel, simd, ArrayND, Element, NumrsError, Result};
+
+/// Largest block that pairwise summation adds up without splitting
+pub(crate) const BLOCK: usize = 128;
+
+/// Number of elements per independently reduced block of a large input
+pub(crate) const REDUCE_BLOCK: usize = 1 << 16;
+
+/// Algorithm used by [`ArrayND::sum_with`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SumMode {
+    /// Recursive pairwise summation, used by `sum`
+    #[default]
+    Pairwise,
+    /// Kahan compensated summation, loses the compensation when an element is
+    /// larger than the running sum
+    Kahan,
+    /// Neumaier's improved Kahan summation, which also handles elements larger
+    /// than the running sum
+    Neumaier,
+}
+
+/// Pairwise sum, adding up the blocks of at most `BLOCK` elements with `leaf`
+pub(crate) fn pairwise_sum<T: Element + Add<Output = T>>(
+    data: &[T],
+    leaf: impl Fn(&[T]) -> T + Copy,
+) -> T {
+    if data.len() <= BLOCK {
+        return leaf(data);
+    }
+    let (left, right) = data.split_at(data.len() / 2);
+    pairwise_sum(left, leaf).wrapping_add(pairwise_sum(right, leaf))
+}
+
+/// Pairwise sum of the products of matching elements
+fn pairwise_dot<T: Element + Add<Output = T> + Mul<Output = T>>(lhs: &[T], rhs: &[T]) -> T {
+    if lhs.len() <= BLOCK {
+        return simd::dot(lhs, rhs);
+    }
+    let mid = lhs.len() / 2;
+    pairwise_dot(&lhs[..mid], &rhs[..mid]).wrapping_add(pairwise_dot(&lhs[mid..], &rhs[mid..]))
+}
+
+/// Position range of block `index` of `REDUCE_BLOCK` elements in an input of length `len`
+fn block_range(index: usize, len: usize) -> Range<usize> {
+    index * REDUCE_BLOCK..((index + 1) * REDUCE_BLOCK).min(len)
+}
+
+/// Applies `f` to the range of every block of `REDUCE_BLOCK` elements in an
+/// input of length `len`, keeping their order
+#[cfg(target_family = "wasm")]
+fn map_blocks<U: Send>(
+    len: usize,
+    _parallel: bool,
+    f: impl Fn(Range<usize>) -> U + Sync + Send,
+) -> Vec<U> {
+    (0..len.div_ceil(REDUCE_BLOCK))
+        .map(|index| f(block_range(index, len)))
+        .collect()
+}
+
+/// Applies `f` to the range of every block of `REDUCE_BLOCK` elements in an
+/// input of length `len`, keeping their order
+#[cfg(target_family = "unix")]
+fn map_blocks<U: Send>(
+    len: usize,
+    parallel: bool,
+    f: impl Fn(Range<usize>) -> U + Sync + Send,
+) -> Vec<U> {
+    let blocks = 0..len.div_ceil(REDUCE_BLOCK);
+    if parallel {
+        blocks
+            .into_par_iter()
+            .map(|index| f(block_range(index, len)))
+            .collect()
+    } else {
+        blocks.map(|index| f(block_range(index, len))).collect()
+    }
+}
+
+/// Pairwise sum with `leaf` summing the smallest blocks, and the blocks of
+/// `REDUCE_BLOCK` elements summed in parallel if `parallel` is set. The result
+/// does not depend on `parallel`.
+pub(crate) fn blocked_sum<T: Element + Add<Output = T>>(
+    data: &[T],
+    parallel: bool,
+    leaf: impl Fn(&[T]) -> T + Copy + Sync + Send,
+) -> T {
+    let partials = map_blocks(data.len(), parallel, |range| {
+        pairwise_sum(&data[range], leaf)
+    });
+    pairwise_sum(&partials, simd::sum)
+}
+
+/// Sum of the products of matching elements, with the blocks reduced in
+/// parallel if `parallel` is set. The result does not depend on `parallel`.
+fn blocked_dot<T: Element + Add<Output = T> + Mul<Output = T>>(
+    lhs: &[T],
+    rhs: &[T],
+    parallel: bool,
+) -> T {
+    let partials = map_blocks(lhs.len(), parallel, |range| {
+        pairwise_dot(&lhs[range.clone()], &rhs[range])
+    });
+    pairwise_sum(&partials, simd::sum)
+}
+
+/// Kahan compensated sum
+fn kahan_sum<T: Element + Add<Output = T> + Sub<Output = T>>(data: &[T]) -> T {
+    let mut sum = T::zero();
+    let mut compensation = T::zero();
+    for &x in data {
+        let y = x.wrapping_sub(compensation);
+        let total = sum.wrapping_add(y);
+        compensation = total.wrapping_sub(sum).wrapping_sub(y);
+        sum = total;
+    }
+    sum
+}
+
+/// Neumaier compensated sum
+fn neumaier_sum<T: Element + Add<Output = T> + Sub<Output = T>>(data: &[T]) -> T {
+    let abs = |x: T| {
+        if x < T::zero() {
+            T::zero().wrapping_sub(x)
+        } else {
+            x
+        }
+    };
+    let mut sum = T::zero();
+    let mut compensation = T::zero();
+    for &x in data {
+        let total = sum.wrapping_add(x);
+        // The error of the addition is exact when computed from the larger operand
+        let error = if abs(sum) >= abs(x) {
+            x.wrapping_sub(total.wrapping_sub(sum))
+        } else {
+            sum.wrapping_sub(total.wrapping_sub(x))
+        };
+        compensation = compensation.wrapping_add(error);
+        sum = total;
+    }
+    sum.wrapping_add(compensation)
+}
+
+impl<T: Element + Add<Output = T> + Sub<Output = T>> ArrayND<T> {
+    /// Sums the data with the given algorithm
+    ///
+    /// `sum_with(SumMode::Pairwise)` is the same as [`sum`](ArrayND::sum).
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, SumMode};
+    /// let array: ArrayND = ArrayND::new(vec![1.0, 1e100, 1.0, -1e100]);
+    /// assert_eq!(array.sum_with(SumMode::Pairwise), 0.0);
+    /// assert_eq!(array.sum_with(SumMode::Neumaier), 2.0);
+    /// ```
+    pub fn sum_with(&self, mode: SumMode) -> T {
+        match mode {
+            SumMode::Pairwise => self.sum(),
+            SumMode::Kahan => kahan_sum(&self.data),
+            SumMode::Neumaier => neumaier_sum(&self.data),
+        }
+    }
+}
+
+impl<T: Element + Add<Output = T> + Mul<Output = T>> ArrayND<T> {
+    /// Inner product of two 1D Arrays of the same length
+    ///
+    /// Summed pairwise in fixed blocks like [`sum`](ArrayND::sum), so the result
+    /// is the same on any number of threads. Panics if the shapes do not match.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let a: ArrayND = ArrayND::new(vec![1., 2., 3.]);
+    /// let b: ArrayND = ArrayND::new(vec![4., 5., 6.]);
+    /// assert_eq!(a.dot(&b), 32.);
+    /// ```
+    pub fn dot(&self, other: &ArrayND<T>) -> T {
+        error::or_panic(self.try_dot(other))
+    }
+
+    /// Inner product of two 1D Arrays, failing unless both are 1D with the same length
+    pub fn try_dot(&self, other: &ArrayND<T>) -> Result<T> {
+        if self.ndim() != 1 || self.shape != other.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "dot requires two 1D arrays of the same length, got shapes {:?} {:?}",
+                self.shape, other.shape
+            )));
+        }
+        let lhs = self.contiguous_data();
+        let rhs = other.contiguous_data();
+        Ok(parallel::run(self.size, |parallel| {
+            blocked_dot(&lhs, &rhs, parallel)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pairwise_sum, SumMode, REDUCE_BLOCK};
+    use crate::{simd, ArrayND};
+
+    /// `n` pairs of 1e16 and 1.0, where every 1.0 is lost by a plain loop
+    fn alternating(n: usize) -> (ArrayND, f64) {
+        let data = (0..2 * n)
+            .map(|i| if i % 2 == 0 { 1e16 } else { 1.0 })
+            .collect();
+        let exact = (n as u128 * 10_000_000_000_000_001) as f64;
+        (ArrayND::new(data), exact)
+    }
+
+    fn naive_sum(data: &[f64]) -> f64 {
+        data.iter().sum()
+    }
+
+    #[test]
+    fn compensated_alternating() {
+        let (array, exact) = alternating(1_000);
+        assert_eq!(naive_sum(&array.to_vec()), 1e19);
+        assert_eq!(array.sum_with(SumMode::Kahan), exact);
+        assert_eq!(array.sum_with(SumMode::Neumaier), exact);
+    }
+
+    #[test]
+    fn neumaier_large_elements() {
+        let array = ArrayND::new(vec![1.0, 1e100, 1.0, -1e100]);
+        assert_eq!(array.sum_with(SumMode::Kahan), 0.0);
+        assert_eq!(array.sum_with(SumMode::Neumaier), 2.0);
+    }
+
+    #[test]
+    fn pairwise_beats_naive() {
+        let data = vec![0.1; 1_000_000];
+        let exact = 100_000.0;
+        let naive_error = (naive_sum(&data) - exact).abs();
+        let pairwise_error = (pairwise_sum(&data, simd::sum) - exact).abs();
+        assert!(naive_error > 1e-6, "{}", naive_error);
+        assert!(pairwise_error < naive_error / 100., "{}", pairwise_error);
+    }
+
+    #[test]
+    fn par_matches_seq_exactly() {
+        let array: ArrayND = ArrayND::random(3_000_001) * 1e8;
+        assert_eq!(array.seq_sum().to_bits(), array.par_sum().to_bits());
+        assert_eq!(array.sum().to_bits(), array.seq_sum().to_bits());
+    }
+
+    #[test]
+    fn integer_modes() {
+        let array: ArrayND<i64> = ArrayND::arange(-500, 1_000, 1);
+        let expected = array.seq_sum();
+        assert_eq!(array.sum_with(SumMode::Kahan), expected);
+        assert_eq!(array.sum_with(SumMode::Neumaier), expected);
+        assert_eq!(array.sum_with(SumMode::Pairwise), expected);
+    }
+
+    #[test]
+    fn dot() {
+        let a: ArrayND<i32> = ArrayND::arange(0, 10, 1);
+        assert_eq!(a.dot(&a), 285);
+        let b = ArrayND::new(vec![1., 2.]);
+        assert!(b.try_dot(&ArrayND::new(vec![1., 2., 3.])).is_err());
+        let matrix = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+        assert!(matrix.try_dot(&matrix).is_err());
+    }
+
+    #[test]
+    fn same_bits_on_any_thread_count() {
+        let a: ArrayND = ArrayND::random(3 * REDUCE_BLOCK + 17) * 1e8 - 5e7;
+        let b: ArrayND = ArrayND::random(3 * REDUCE_BLOCK + 17);
+        let reductions = |a: &ArrayND, b: &ArrayND| {
+            [
+                a.par_sum().to_bits(),
+                a.nansum().to_bits(),
+                a.mean().to_bits(),
+                a.dot(b).to_bits(),
+            ]
+        };
+        let expected = [
+            a.seq_sum().to_bits(),
+            a.seq_sum().to_bits(),
+            (a.seq_sum() / a.shape()[0] as f64).to_bits(),
+            super::blocked_dot(&a.to_vec(), &b.to_vec(), false).to_bits(),
+        ];
+        for threads in [1, 2, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            assert_eq!(pool.install(|| reductions(&a, &b)), expected, "{}", threads);
+        }
+    }
+}
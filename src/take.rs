@@ -0,0 +1,435 @@
+//! Integer-array indexing: `take`, `put`, `gather` and `scatter_add`
+//!
+//! Unlike slicing these copy the selected elements, since the indices can
+//! repeat and come in any order. Every method takes an [`IndexMode`] deciding
+//! what happens to indices outside of the axis.
+
+use std::ops::Add;
+
+use crate::{error, layout, ArrayND, Element, NumrsError, Result};
+
+/// How out of bounds indices are handled by `take`, `put`, `gather` and `scatter_add`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IndexMode {
+    /// Fail on indices outside of the axis, negative indices count from the end
+    #[default]
+    Raise,
+    /// Wrap indices around the axis, so `-1` is the last position and `len` the first
+    Wrap,
+    /// Clamp indices to the first and last position
+    Clip,
+}
+
+/// Position along an axis of length `len` that `index` refers to under `mode`
+fn resolve_index(index: isize, len: usize, mode: IndexMode) -> Result<usize> {
+    if len == 0 {
+        return Err(NumrsError::IndexOutOfBounds(format!(
+            "cannot take index {} from an empty axis",
+            index
+        )));
+    }
+    let len = len as isize;
+    let resolved = match mode {
+        IndexMode::Raise if index < -len || index >= len => {
+            return Err(NumrsError::IndexOutOfBounds(format!(
+                "index {} is out of bounds for axis of length {}",
+                index, len
+            )))
+        }
+        IndexMode::Raise if index < 0 => index + len,
+        IndexMode::Raise => index,
+        IndexMode::Wrap => index.rem_euclid(len),
+        IndexMode::Clip => index.clamp(0, len - 1),
+    };
+    Ok(resolved as usize)
+}
+
+/// Shape and strides without `axis`
+fn remove_axis(shape: &[usize], strides: &[isize], axis: usize) -> (Vec<usize>, Vec<isize>) {
+    let mut shape = shape.to_vec();
+    let mut strides = strides.to_vec();
+    shape.remove(axis);
+    strides.remove(axis);
+    (shape, strides)
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Copies the positions `indices` along `axis`, which can repeat and come in any order
+    ///
+    /// The result has the shape of the Array with `axis` resized to the number
+    /// of indices. Without an axis the indices refer to elements in row-major
+    /// order and the result is 1D.
+    ///
+    /// Panics if `axis` or, with `IndexMode::Raise`, an index is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, IndexMode};
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![3, 2]);
+    /// let rows = array.take(&[2, 0, -1], Some(0), IndexMode::Raise);
+    /// assert_eq!(rows.to_vec(), vec![5., 6., 1., 2., 5., 6.]);
+    /// assert_eq!(array.take(&[1, 7], None, IndexMode::Clip).to_vec(), vec![2., 6.]);
+    /// ```
+    pub fn take(&self, indices: &[isize], axis: Option<usize>, mode: IndexMode) -> ArrayND<T> {
+        error::or_panic(self.try_take(indices, axis, mode))
+    }
+
+    /// Copies the positions `indices` along `axis`, failing if `axis` or, with
+    /// `IndexMode::Raise`, an index is out of bounds
+    pub fn try_take(
+        &self,
+        indices: &[isize],
+        axis: Option<usize>,
+        mode: IndexMode,
+    ) -> Result<ArrayND<T>> {
+        let Some(axis) = axis else {
+            let data = indices
+                .iter()
+                .map(|&index| {
+                    let flat = resolve_index(index, self.size, mode)?;
+                    Ok(self.data[layout::flat_offset(0, &self.shape, &self.strides, flat)?])
+                })
+                .collect::<Result<Vec<T>>>()?;
+            return Ok(ArrayND::new(data));
+        };
+        layout::check_axis(axis, self.ndim())?;
+        let positions = indices
+            .iter()
+            .map(|&index| resolve_index(index, self.shape[axis], mode))
+            .collect::<Result<Vec<usize>>>()?;
+
+        let (outer_shape, outer_strides) = remove_axis(&self.shape, &self.strides, axis);
+        let bases: Vec<usize> = layout::Offsets::new(0, &outer_shape, &outer_strides).collect();
+        let inner: usize = self.shape[axis + 1..].iter().product();
+        let stride = self.strides[axis];
+
+        let mut data = Vec::with_capacity(bases.len() / inner.max(1) * positions.len() * inner);
+        for chunk in bases.chunks(inner.max(1)) {
+            for &position in &positions {
+                let step = position as isize * stride;
+                data.extend(
+                    chunk
+                        .iter()
+                        .map(|&base| self.data[(base as isize + step) as usize]),
+                );
+            }
+        }
+        let mut shape = self.shape.clone();
+        shape[axis] = positions.len();
+        ArrayND::try_from_shape_vec(data, shape)
+    }
+
+    /// Overwrites the elements at `indices`, counted in row-major order, with `values`
+    ///
+    /// `values` is repeated if it is shorter than `indices`, and later indices
+    /// win when one repeats. Panics if `values` is empty or, with
+    /// `IndexMode::Raise`, an index is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, IndexMode};
+    /// let mut array: ArrayND<i32> = ArrayND::new(vec![0, 0, 0, 0]);
+    /// array.put(&[0, -1, 5], &[7, 8], IndexMode::Wrap);
+    /// assert_eq!(array.to_vec(), vec![7, 7, 0, 8]);
+    /// ```
+    pub fn put(&mut self, indices: &[isize], values: &[T], mode: IndexMode) {
+        error::or_panic(self.try_put(indices, values, mode))
+    }
+
+    /// Overwrites the elements at `indices` with `values`, failing without
+    /// modifying the Array if `values` is empty or an index is out of bounds
+    pub fn try_put(&mut self, indices: &[isize], values: &[T], mode: IndexMode) -> Result<()> {
+        if values.is_empty() && !indices.is_empty() {
+            return Err(NumrsError::ShapeMismatch(
+                "cannot put an empty list of values".to_string(),
+            ));
+        }
+        let offsets = indices
+            .iter()
+            .map(|&index| {
+                let flat = resolve_index(index, self.size, mode)?;
+                layout::flat_offset(0, &self.shape, &self.strides, flat)
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        for (offset, &value) in offsets.into_iter().zip(values.iter().cycle()) {
+            self.data[offset] = value;
+        }
+        self.invalidate_stats();
+        Ok(())
+    }
+
+    /// Picks one element along `axis` for every entry of `indices`
+    ///
+    /// `indices` has as many axes as the Array and at most as many positions
+    /// along every other axis, the result has its shape. For `axis` 0 of a 2D
+    /// Array, `result[i][j] = self[indices[i][j]][j]`.
+    ///
+    /// Panics if the shapes do not fit or, with `IndexMode::Raise`, an index is
+    /// out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, IndexMode};
+    /// let array: ArrayND = ArrayND::from_shape_vec(vec![1., 2., 3., 4., 5., 6.], vec![2, 3]);
+    /// let indices = ArrayND::from_shape_vec(vec![2, 0, 1, 1], vec![2, 2]);
+    /// let gathered = array.gather(1, &indices, IndexMode::Raise);
+    /// assert_eq!(gathered.to_vec(), vec![3., 1., 5., 5.]);
+    /// ```
+    pub fn gather(&self, axis: usize, indices: &ArrayND<i64>, mode: IndexMode) -> ArrayND<T> {
+        error::or_panic(self.try_gather(axis, indices, mode))
+    }
+
+    /// Picks one element along `axis` for every entry of `indices`, failing if
+    /// the shapes do not fit or, with `IndexMode::Raise`, an index is out of bounds
+    pub fn try_gather(
+        &self,
+        axis: usize,
+        indices: &ArrayND<i64>,
+        mode: IndexMode,
+    ) -> Result<ArrayND<T>> {
+        let offsets = self.gather_offsets(axis, indices, mode)?;
+        let data = offsets.into_iter().map(|i| self.data[i]).collect();
+        ArrayND::try_from_shape_vec(data, indices.shape.clone())
+    }
+
+    /// Offsets in the buffer of the elements `indices` picks along `axis`,
+    /// in row-major order of `indices`
+    fn gather_offsets(
+        &self,
+        axis: usize,
+        indices: &ArrayND<i64>,
+        mode: IndexMode,
+    ) -> Result<Vec<usize>> {
+        layout::check_axis(axis, self.ndim())?;
+        let fits = indices.ndim() == self.ndim()
+            && (0..self.ndim()).all(|d| d == axis || indices.shape[d] <= self.shape[d]);
+        if !fits {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "indices of shape {:?} do not fit array of shape {:?} along axis {}",
+                indices.shape, self.shape, axis
+            )));
+        }
+        let mut strides = self.strides.clone();
+        strides[axis] = 0;
+        layout::Offsets::new(0, &indices.shape, &strides)
+            .zip(indices.iter())
+            .map(|(base, &index)| {
+                let position = resolve_index(index as isize, self.shape[axis], mode)?;
+                Ok((base as isize + position as isize * self.strides[axis]) as usize)
+            })
+            .collect()
+    }
+}
+
+impl<T: Element + Add<Output = T>> ArrayND<T> {
+    /// Adds every element of `src` to the element `indices` points at along `axis`
+    ///
+    /// The inverse of [`gather`](ArrayND::gather): for `axis` 0 of a 2D Array,
+    /// `self[indices[i][j]][j] += src[i][j]`. Repeated indices accumulate, which
+    /// makes this a grouped sum. `src` must have the shape of `indices`.
+    ///
+    /// Panics if the shapes do not fit or, with `IndexMode::Raise`, an index is
+    /// out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, IndexMode};
+    /// let mut totals: ArrayND = ArrayND::new(vec![0., 0.]);
+    /// let groups = ArrayND::new(vec![0, 1, 0, 1]);
+    /// let values = ArrayND::new(vec![1., 2., 3., 4.]);
+    /// totals.scatter_add(0, &groups, &values, IndexMode::Raise);
+    /// assert_eq!(totals.to_vec(), vec![4., 6.]);
+    /// ```
+    pub fn scatter_add(
+        &mut self,
+        axis: usize,
+        indices: &ArrayND<i64>,
+        src: &ArrayND<T>,
+        mode: IndexMode,
+    ) {
+        error::or_panic(self.try_scatter_add(axis, indices, src, mode))
+    }
+
+    /// Adds every element of `src` to the element `indices` points at along
+    /// `axis`, failing without modifying the Array if the shapes do not fit or,
+    /// with `IndexMode::Raise`, an index is out of bounds
+    pub fn try_scatter_add(
+        &mut self,
+        axis: usize,
+        indices: &ArrayND<i64>,
+        src: &ArrayND<T>,
+        mode: IndexMode,
+    ) -> Result<()> {
+        if src.shape != indices.shape {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "source of shape {:?} does not match indices of shape {:?}",
+                src.shape, indices.shape
+            )));
+        }
+        let offsets = self.gather_offsets(axis, indices, mode)?;
+        for (offset, &value) in offsets.into_iter().zip(src.iter()) {
+            self.data[offset] = self.data[offset].wrapping_add(value);
+        }
+        self.invalidate_stats();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_index, IndexMode};
+    use crate::{ArrayND, NumrsError};
+
+    fn get_matrix() -> ArrayND<i32> {
+        ArrayND::from_shape_vec((0..12).collect(), vec![3, 4])
+    }
+
+    #[test]
+    fn index_modes() {
+        assert_eq!(resolve_index(-1, 4, IndexMode::Raise).unwrap(), 3);
+        assert!(resolve_index(4, 4, IndexMode::Raise).is_err());
+        assert!(resolve_index(-5, 4, IndexMode::Raise).is_err());
+        assert_eq!(resolve_index(5, 4, IndexMode::Wrap).unwrap(), 1);
+        assert_eq!(resolve_index(-5, 4, IndexMode::Wrap).unwrap(), 3);
+        assert_eq!(resolve_index(9, 4, IndexMode::Clip).unwrap(), 3);
+        assert_eq!(resolve_index(-9, 4, IndexMode::Clip).unwrap(), 0);
+        assert!(matches!(
+            resolve_index(0, 0, IndexMode::Wrap),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn take_rows_and_columns() {
+        let array = get_matrix();
+        let rows = array.take(&[2, 2, 0], Some(0), IndexMode::Raise);
+        assert_eq!(rows.shape(), &[3, 4]);
+        assert_eq!(rows.to_vec(), vec![8, 9, 10, 11, 8, 9, 10, 11, 0, 1, 2, 3]);
+
+        let columns = array.take(&[3, 0], Some(1), IndexMode::Raise);
+        assert_eq!(columns.shape(), &[3, 2]);
+        assert_eq!(columns.to_vec(), vec![3, 0, 7, 4, 11, 8]);
+        assert_eq!(array.take(&[], Some(1), IndexMode::Raise).shape(), &[3, 0]);
+    }
+
+    #[test]
+    fn take_transposed_and_flat() {
+        let transposed = get_matrix().transpose();
+        let rows = transposed.take(&[1], Some(0), IndexMode::Raise);
+        assert_eq!(rows.to_vec(), vec![1, 5, 9]);
+        assert_eq!(
+            transposed
+                .take(&[0, 1, -1], None, IndexMode::Raise)
+                .to_vec(),
+            vec![0, 4, 11]
+        );
+    }
+
+    #[test]
+    fn take_errors() {
+        let array = get_matrix();
+        assert!(matches!(
+            array.try_take(&[3], Some(0), IndexMode::Raise),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(matches!(
+            array.try_take(&[0], Some(2), IndexMode::Raise),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert_eq!(
+            array.take(&[3, -4], Some(0), IndexMode::Wrap).to_vec(),
+            array.take(&[0, 2], Some(0), IndexMode::Raise).to_vec()
+        );
+    }
+
+    #[test]
+    fn put_values() {
+        let mut array = get_matrix().transpose();
+        assert_eq!(array.max(), 11);
+        array.put(&[0, 1, 2], &[-1], IndexMode::Raise);
+        assert_eq!(array.to_vec()[..4], [-1, -1, -1, 1]);
+        assert_eq!(array.min(), -1);
+
+        array.put(&[100], &[50], IndexMode::Clip);
+        assert_eq!(array[[3, 2]], 50);
+        assert_eq!(array.max(), 50);
+    }
+
+    #[test]
+    fn put_errors_leave_array_unchanged() {
+        let mut array = get_matrix();
+        assert!(matches!(
+            array.try_put(&[0, 12], &[1, 2], IndexMode::Raise),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(matches!(
+            array.try_put(&[0], &[], IndexMode::Raise),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        assert_eq!(array, get_matrix());
+    }
+
+    #[test]
+    fn gather_along_axes() {
+        let array = get_matrix();
+        let indices = ArrayND::from_shape_vec(vec![2, 0, 1, 2], vec![1, 4]);
+        assert_eq!(
+            array.gather(0, &indices, IndexMode::Raise).to_vec(),
+            vec![8, 1, 6, 11]
+        );
+
+        let indices = ArrayND::from_shape_vec(vec![-1, 0], vec![2, 1]);
+        let gathered = array.gather(1, &indices, IndexMode::Raise);
+        assert_eq!(gathered.shape(), &[2, 1]);
+        assert_eq!(gathered.to_vec(), vec![3, 4]);
+    }
+
+    #[test]
+    fn gather_errors() {
+        let array = get_matrix();
+        assert!(matches!(
+            array.try_gather(0, &ArrayND::new(vec![0, 1]), IndexMode::Raise),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        let too_wide = ArrayND::from_shape_vec(vec![0; 5], vec![1, 5]);
+        assert!(matches!(
+            array.try_gather(0, &too_wide, IndexMode::Raise),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        let indices = ArrayND::from_shape_vec(vec![3], vec![1, 1]);
+        assert!(matches!(
+            array.try_gather(0, &indices, IndexMode::Raise),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn scatter_add_groups_rows() {
+        let mut totals: ArrayND<i32> = ArrayND::from_shape_vec(vec![0; 4], vec![2, 2]);
+        let groups = ArrayND::from_shape_vec(vec![1, 1, 0, 0, 1, 1], vec![3, 2]);
+        let values = ArrayND::from_shape_vec(vec![1, 2, 3, 4, 5, 6], vec![3, 2]);
+        totals.scatter_add(0, &groups, &values, IndexMode::Raise);
+
+        assert_eq!(totals.to_vec(), vec![3, 4, 6, 8]);
+        assert_eq!(totals.max(), 8);
+    }
+
+    #[test]
+    fn scatter_add_errors_leave_array_unchanged() {
+        let mut array = get_matrix();
+        let indices = ArrayND::from_shape_vec(vec![0, 5], vec![1, 2]);
+        let src = ArrayND::from_shape_vec(vec![1, 1], vec![1, 2]);
+        assert!(matches!(
+            array.try_scatter_add(1, &indices, &src, IndexMode::Raise),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(matches!(
+            array.try_scatter_add(1, &indices, &ArrayND::new(vec![1, 1]), IndexMode::Raise),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        assert_eq!(array, get_matrix());
+
+        array.scatter_add(1, &indices, &src, IndexMode::Clip);
+        assert_eq!(array.to_vec()[..4], [1, 1, 2, 4]);
+    }
+}
@@ -0,0 +1,1096 @@
+//! Universal functions: element-wise functions applied with the Array machinery
+//!
+//! A [`UnaryUfunc`] or [`BinaryUfunc`] wraps a function on elements, either
+//! one of the built-in [`Builtin`] functions or a closure, and applies it to
+//! whole Arrays with the same kernels as the operators: operands are
+//! broadcast together, large contiguous Arrays are split across threads (see
+//! [`Parallelism`](crate::Parallelism)) and the built-in arithmetic uses the
+//! vector kernels.
+//!
+//! Results can be written into a new Array, in place, or into an existing
+//! `out` Array, optionally only where a `where` mask is `true`. Functions that
+//! are defined for every numeric element type also apply to a
+//! [`DynArray`](crate::DynArray), promoting the operands first.
+//!
+//! Binary functions also reduce an Array along an axis, accumulate running
+//! results like a cumulative sum, reduce slices of an axis and combine every
+//! element of one Array with every element of another.
+//!
+//! # Example
+//! ```
+//! use numrs::{ArrayND, BinaryUfunc};
+//! let a: ArrayND = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+//! let b: ArrayND = ArrayND::new(vec![10., 20.]);
+//! assert_eq!(BinaryUfunc::ADD.call(&a, &b).to_vec(), vec![11., 22., 13., 24.]);
+//!
+//! let diff = BinaryUfunc::new(|x: f64, y: f64| (x - y).abs());
+//! let mut out = ArrayND::from_shape_vec(vec![0.; 4], vec![2, 2]);
+//! diff.call_where(&a, &b, &mut out, &a.gt_scalar(2.));
+//! assert_eq!(out.to_vec(), vec![0., 0., 7., 16.]);
+//! ```
+
+#[cfg(target_family = "unix")]
+use rayon::{
+    iter::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    error, layout, ops, parallel,
+    simd::{self, Arith},
+    ArrayND, Element, NumrsError, Result,
+};
+
+/// Function of one element, applied by a [`UnaryUfunc`]
+///
+/// Implemented for every `Fn(T) -> T` closure that can be shared across threads.
+pub trait UnaryOp<T>: Sync + Send {
+    fn apply(&self, x: T) -> T;
+}
+
+impl<T, F: Fn(T) -> T + Sync + Send> UnaryOp<T> for F {
+    fn apply(&self, x: T) -> T {
+        self(x)
+    }
+}
+
+/// Function of two elements, applied by a [`BinaryUfunc`]
+///
+/// Implemented for every `Fn(T, T) -> T` closure that can be shared across threads.
+pub trait BinaryOp<T>: Sync + Send {
+    fn apply(&self, x: T, y: T) -> T;
+
+    /// The function of `x` and `y`, or `None` where it is undefined, like an
+    /// integer division by zero, which makes the ufunc fail
+    fn checked_apply(&self, x: T, y: T) -> Option<T> {
+        Some(self.apply(x, y))
+    }
+
+    /// Result of reducing no elements, if the function has one, like 0 for addition
+    fn identity(&self) -> Option<T> {
+        None
+    }
+}
+
+impl<T, F: Fn(T, T) -> T + Sync + Send> BinaryOp<T> for F {
+    fn apply(&self, x: T, y: T) -> T {
+        self(x, y)
+    }
+}
+
+/// [`UnaryOp`] for every numeric element type, which can be applied to a `DynArray`
+pub trait NumericUnaryOp:
+    UnaryOp<i8>
+    + UnaryOp<i16>
+    + UnaryOp<i32>
+    + UnaryOp<i64>
+    + UnaryOp<u8>
+    + UnaryOp<u16>
+    + UnaryOp<u32>
+    + UnaryOp<u64>
+    + UnaryOp<f32>
+    + UnaryOp<f64>
+{
+}
+
+impl<F> NumericUnaryOp for F where
+    F: UnaryOp<i8>
+        + UnaryOp<i16>
+        + UnaryOp<i32>
+        + UnaryOp<i64>
+        + UnaryOp<u8>
+        + UnaryOp<u16>
+        + UnaryOp<u32>
+        + UnaryOp<u64>
+        + UnaryOp<f32>
+        + UnaryOp<f64>
+{
+}
+
+/// [`BinaryOp`] for every numeric element type, which can be applied to a `DynArray`
+pub trait NumericBinaryOp:
+    BinaryOp<i8>
+    + BinaryOp<i16>
+    + BinaryOp<i32>
+    + BinaryOp<i64>
+    + BinaryOp<u8>
+    + BinaryOp<u16>
+    + BinaryOp<u32>
+    + BinaryOp<u64>
+    + BinaryOp<f32>
+    + BinaryOp<f64>
+{
+}
+
+impl<F> NumericBinaryOp for F where
+    F: BinaryOp<i8>
+        + BinaryOp<i16>
+        + BinaryOp<i32>
+        + BinaryOp<i64>
+        + BinaryOp<u8>
+        + BinaryOp<u16>
+        + BinaryOp<u32>
+        + BinaryOp<u64>
+        + BinaryOp<f32>
+        + BinaryOp<f64>
+{
+}
+
+/// Built-in binary functions, defined for every numeric element type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Builtin {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Larger of the two elements, NaN if either is NaN
+    Maximum,
+    /// Smaller of the two elements, NaN if either is NaN
+    Minimum,
+}
+
+/// Integer `Add`, `Sub` and `Mul` wrap on overflow like the operators of
+/// `ArrayND`, and an integer `Div` by zero or overflowing is undefined.
+impl<T: Element> BinaryOp<T> for Builtin {
+    fn apply(&self, x: T, y: T) -> T {
+        match self {
+            Builtin::Add => x.wrapping_add(y),
+            Builtin::Sub => x.wrapping_sub(y),
+            Builtin::Mul => x.wrapping_mul(y),
+            Builtin::Div => x
+                .checked_div(y)
+                .expect("attempt to divide by zero or with overflow"),
+            Builtin::Maximum if x.is_nan() || x >= y => x,
+            Builtin::Minimum if x.is_nan() || x <= y => x,
+            Builtin::Maximum | Builtin::Minimum => y,
+        }
+    }
+
+    fn checked_apply(&self, x: T, y: T) -> Option<T> {
+        match self {
+            Builtin::Div => x.checked_div(y),
+            _ => Some(self.apply(x, y)),
+        }
+    }
+
+    fn identity(&self) -> Option<T> {
+        match self {
+            Builtin::Add => Some(T::zero()),
+            Builtin::Mul => Some(T::one()),
+            _ => None,
+        }
+    }
+}
+
+/// Element-wise function of one Array
+///
+/// # Example
+/// ```
+/// use numrs::{ArrayND, UnaryUfunc};
+/// let clamp = UnaryUfunc::new(|x: i32| x.clamp(0, 10));
+/// let array = ArrayND::new(vec![-5, 5, 15]);
+/// assert_eq!(clamp.call(&array).to_vec(), vec![0, 5, 10]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct UnaryUfunc<F> {
+    op: F,
+}
+
+impl<F> UnaryUfunc<F> {
+    /// Ufunc applying `op` to every element
+    pub fn new(op: F) -> UnaryUfunc<F> {
+        UnaryUfunc { op }
+    }
+
+    /// The function applied to every element
+    pub fn op(&self) -> &F {
+        &self.op
+    }
+
+    /// Applies the function to every element into a new Array
+    pub fn call<T: Element>(&self, array: &ArrayND<T>) -> ArrayND<T>
+    where
+        F: UnaryOp<T>,
+    {
+        ops::map_new(array, None, |x| self.op.apply(x))
+    }
+
+    /// Replaces every element with the function applied to it
+    pub fn call_mut<T: Element>(&self, array: &mut ArrayND<T>)
+    where
+        F: UnaryOp<T>,
+    {
+        array.map_assign(None, |x| self.op.apply(x))
+    }
+
+    /// Writes the function of every element into `out`
+    ///
+    /// Panics if the Array cannot be broadcast to the shape of `out`.
+    pub fn call_into<T: Element>(&self, array: &ArrayND<T>, out: &mut ArrayND<T>)
+    where
+        F: UnaryOp<T>,
+    {
+        error::or_panic(self.try_call_into(array, out))
+    }
+
+    /// Writes the function of every element into `out`, failing without
+    /// writing anything if the Array cannot be broadcast to its shape
+    pub fn try_call_into<T: Element>(&self, array: &ArrayND<T>, out: &mut ArrayND<T>) -> Result<()>
+    where
+        F: UnaryOp<T>,
+    {
+        let operand = array.operand();
+        ops::zip_to(&operand, &operand, out, None, None, |x, _| self.op.apply(x))
+    }
+
+    /// Writes the function of every element into `out` where `mask` is
+    /// `true`, leaving the other elements of `out` unchanged
+    ///
+    /// Panics if the Array or the mask cannot be broadcast to the shape of `out`.
+    pub fn call_where<T: Element>(
+        &self,
+        array: &ArrayND<T>,
+        out: &mut ArrayND<T>,
+        mask: &ArrayND<bool>,
+    ) where
+        F: UnaryOp<T>,
+    {
+        error::or_panic(self.try_call_where(array, out, mask))
+    }
+
+    /// Writes the function of every element into `out` where `mask` is
+    /// `true`, failing without writing anything if the Array or the mask
+    /// cannot be broadcast to the shape of `out`
+    pub fn try_call_where<T: Element>(
+        &self,
+        array: &ArrayND<T>,
+        out: &mut ArrayND<T>,
+        mask: &ArrayND<bool>,
+    ) -> Result<()>
+    where
+        F: UnaryOp<T>,
+    {
+        let operand = array.operand();
+        ops::zip_to(
+            &operand,
+            &operand,
+            out,
+            Some(&mask.operand()),
+            None,
+            |x, _| self.op.apply(x),
+        )
+    }
+}
+
+/// Element-wise function of two Arrays, broadcast together
+///
+/// The built-in functions are available as constants like [`BinaryUfunc::ADD`],
+/// any closure of two elements can be wrapped with [`BinaryUfunc::new`].
+///
+/// # Example
+/// ```
+/// use numrs::{ArrayND, BinaryUfunc};
+/// let a = ArrayND::new(vec![1, 7, 3]);
+/// let b = ArrayND::new(vec![4, 2, 6]);
+/// assert_eq!(BinaryUfunc::MAXIMUM.call(&a, &b).to_vec(), vec![4, 7, 6]);
+///
+/// let gcd = BinaryUfunc::new(|mut x: i32, mut y: i32| {
+///     while y != 0 {
+///         (x, y) = (y, x % y);
+///     }
+///     x
+/// });
+/// assert_eq!(gcd.call(&ArrayND::new(vec![12, 9]), &ArrayND::new(vec![18])).to_vec(), vec![6, 9]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct BinaryUfunc<F> {
+    op: F,
+    /// Vector kernel computing the same function, if there is one
+    arith: Option<Arith>,
+}
+
+impl BinaryUfunc<Builtin> {
+    /// `x + y`
+    pub const ADD: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Add, Some(Arith::Add));
+    /// `x - y`
+    pub const SUB: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Sub, Some(Arith::Sub));
+    /// `x * y`
+    pub const MUL: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Mul, Some(Arith::Mul));
+    /// `x / y`
+    pub const DIV: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Div, Some(Arith::Div));
+    /// Larger of `x` and `y`, NaN if either is NaN
+    pub const MAXIMUM: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Maximum, None);
+    /// Smaller of `x` and `y`, NaN if either is NaN
+    pub const MINIMUM: BinaryUfunc<Builtin> = BinaryUfunc::builtin(Builtin::Minimum, None);
+
+    const fn builtin(op: Builtin, arith: Option<Arith>) -> BinaryUfunc<Builtin> {
+        BinaryUfunc { op, arith }
+    }
+}
+
+impl<F> BinaryUfunc<F> {
+    /// Ufunc applying `op` between matching elements
+    pub fn new(op: F) -> BinaryUfunc<F> {
+        BinaryUfunc { op, arith: None }
+    }
+
+    /// The function applied between matching elements
+    pub fn op(&self) -> &F {
+        &self.op
+    }
+
+    /// Applies the function between matching elements into a new Array
+    ///
+    /// Panics if the shapes cannot be broadcast together.
+    pub fn call<T: Element>(&self, lhs: &ArrayND<T>, rhs: &ArrayND<T>) -> ArrayND<T>
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_call(lhs, rhs))
+    }
+
+    /// Applies the function between matching elements into a new Array,
+    /// failing if the shapes are incompatible or the function is undefined
+    /// for some of them
+    pub fn try_call<T: Element>(&self, lhs: &ArrayND<T>, rhs: &ArrayND<T>) -> Result<ArrayND<T>>
+    where
+        F: BinaryOp<T>,
+    {
+        checked(|undefined| {
+            ops::zip_new(&lhs.operand(), &rhs.operand(), self.arith, |x, y| {
+                self.apply(x, y, undefined)
+            })
+        })
+    }
+
+    /// Replaces every element of `lhs` with the function of it and the matching element of `rhs`
+    ///
+    /// Panics if `rhs` cannot be broadcast to the shape of `lhs`.
+    pub fn call_mut<T: Element>(&self, lhs: &mut ArrayND<T>, rhs: &ArrayND<T>)
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_call_mut(lhs, rhs))
+    }
+
+    /// Replaces every element of `lhs` with the function of it and the
+    /// matching element of `rhs`, failing without modifying `lhs` if `rhs`
+    /// cannot be broadcast to its shape
+    ///
+    /// Where the function is undefined it fails after updating the other elements.
+    pub fn try_call_mut<T: Element>(&self, lhs: &mut ArrayND<T>, rhs: &ArrayND<T>) -> Result<()>
+    where
+        F: BinaryOp<T>,
+    {
+        checked(|undefined| {
+            lhs.zip_assign(&rhs.operand(), self.arith, |x, y| {
+                self.apply(x, y, undefined)
+            })
+        })
+    }
+
+    /// Writes the function between matching elements into `out`
+    ///
+    /// Panics if the operands cannot be broadcast to the shape of `out`.
+    pub fn call_into<T: Element>(&self, lhs: &ArrayND<T>, rhs: &ArrayND<T>, out: &mut ArrayND<T>)
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_call_into(lhs, rhs, out))
+    }
+
+    /// Writes the function between matching elements into `out`, failing
+    /// without writing anything if the operands cannot be broadcast to its shape
+    ///
+    /// Where the function is undefined it fails after writing the other elements.
+    pub fn try_call_into<T: Element>(
+        &self,
+        lhs: &ArrayND<T>,
+        rhs: &ArrayND<T>,
+        out: &mut ArrayND<T>,
+    ) -> Result<()>
+    where
+        F: BinaryOp<T>,
+    {
+        checked(|undefined| {
+            ops::zip_to(
+                &lhs.operand(),
+                &rhs.operand(),
+                out,
+                None,
+                self.arith,
+                |x, y| self.apply(x, y, undefined),
+            )
+        })
+    }
+
+    /// Writes the function between matching elements into `out` where `mask`
+    /// is `true`, leaving the other elements of `out` unchanged
+    ///
+    /// Panics if the operands or the mask cannot be broadcast to the shape of `out`.
+    pub fn call_where<T: Element>(
+        &self,
+        lhs: &ArrayND<T>,
+        rhs: &ArrayND<T>,
+        out: &mut ArrayND<T>,
+        mask: &ArrayND<bool>,
+    ) where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_call_where(lhs, rhs, out, mask))
+    }
+
+    /// Writes the function between matching elements into `out` where `mask`
+    /// is `true`, failing without writing anything if the operands or the
+    /// mask cannot be broadcast to the shape of `out`
+    ///
+    /// Where the function is undefined it fails after writing the other elements.
+    pub fn try_call_where<T: Element>(
+        &self,
+        lhs: &ArrayND<T>,
+        rhs: &ArrayND<T>,
+        out: &mut ArrayND<T>,
+        mask: &ArrayND<bool>,
+    ) -> Result<()>
+    where
+        F: BinaryOp<T>,
+    {
+        let mask = mask.operand();
+        checked(|undefined| {
+            ops::zip_to(
+                &lhs.operand(),
+                &rhs.operand(),
+                out,
+                Some(&mask),
+                None,
+                |x, y| self.apply(x, y, undefined),
+            )
+        })
+    }
+}
+
+impl<F> BinaryUfunc<F> {
+    /// Combines the elements along `axis` from first to last, removing the
+    /// axis or keeping it with length 1 when `keepdims` is set
+    ///
+    /// Empty lanes reduce to the identity of the function. Panics if `axis` is
+    /// out of bounds, or if there is an empty lane and no identity.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, BinaryUfunc};
+    /// let array = ArrayND::from_shape_vec(vec![3, 1, 4, 1, 5, 9], vec![2, 3]);
+    /// assert_eq!(BinaryUfunc::MUL.reduce(&array, 1, false).to_vec(), vec![12, 45]);
+    /// assert_eq!(BinaryUfunc::MAXIMUM.reduce(&array, 0, true).to_vec(), vec![3, 5, 9]);
+    /// ```
+    pub fn reduce<T: Element>(&self, array: &ArrayND<T>, axis: usize, keepdims: bool) -> ArrayND<T>
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_reduce(array, axis, keepdims))
+    }
+
+    /// Combines the elements along `axis` from first to last, failing if
+    /// `axis` is out of bounds, there is an empty lane and no identity, or the
+    /// function is undefined for some of the elements
+    pub fn try_reduce<T: Element>(
+        &self,
+        array: &ArrayND<T>,
+        axis: usize,
+        keepdims: bool,
+    ) -> Result<ArrayND<T>>
+    where
+        F: BinaryOp<T>,
+    {
+        layout::check_axis(axis, array.ndim())?;
+        let (outer, len, inner) = split_shape(array.shape(), axis);
+        let mut shape = array.shape().to_vec();
+        if keepdims {
+            shape[axis] = 1;
+        } else {
+            shape.remove(axis);
+        }
+
+        let mut out = vec![T::zero(); outer * inner];
+        if len == 0 && !out.is_empty() {
+            let identity = self.op.identity().ok_or_else(|| {
+                NumrsError::EmptyReduction(
+                    "attempt to reduce an empty lane without an identity".to_string(),
+                )
+            })?;
+            out.fill(identity);
+        } else if len > 0 {
+            let data = array.contiguous_data();
+            checked(|undefined| {
+                parallel::run(data.len(), |parallel| {
+                    for_each_block(&mut out, inner, parallel, |i, row| {
+                        let lanes = &data[i * len * inner..][..len * inner];
+                        row.copy_from_slice(&lanes[..inner]);
+                        for next in lanes[inner..].chunks(inner) {
+                            self.combine(row, next, undefined);
+                        }
+                    })
+                });
+                Ok(())
+            })?;
+        }
+        ArrayND::try_from_shape_vec(out, shape)
+    }
+
+    /// Running results of combining the elements along `axis`, with the shape of the Array
+    ///
+    /// Element `k` along the axis is the reduction of the elements up to and
+    /// including `k`, so [`ADD`](BinaryUfunc::ADD) gives the cumulative sum
+    /// and [`MAXIMUM`](BinaryUfunc::MAXIMUM) the running maximum. Panics if
+    /// `axis` is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, BinaryUfunc};
+    /// let array = ArrayND::from_shape_vec(vec![1, 2, 3, 4, 5, 6], vec![2, 3]);
+    /// assert_eq!(BinaryUfunc::ADD.accumulate(&array, 1).to_vec(), vec![1, 3, 6, 4, 9, 15]);
+    /// assert_eq!(BinaryUfunc::MUL.accumulate(&array, 0).to_vec(), vec![1, 2, 3, 4, 10, 18]);
+    /// ```
+    pub fn accumulate<T: Element>(&self, array: &ArrayND<T>, axis: usize) -> ArrayND<T>
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_accumulate(array, axis))
+    }
+
+    /// Running results of combining the elements along `axis`, failing if
+    /// `axis` is out of bounds or the function is undefined for some of the elements
+    pub fn try_accumulate<T: Element>(&self, array: &ArrayND<T>, axis: usize) -> Result<ArrayND<T>>
+    where
+        F: BinaryOp<T>,
+    {
+        layout::check_axis(axis, array.ndim())?;
+        let (_, len, inner) = split_shape(array.shape(), axis);
+        let mut data = array.to_vec();
+        let arith = self.arith.map(Arith::swapped);
+        checked(|undefined| {
+            parallel::run(data.len(), |parallel| {
+                for_each_block(&mut data, len * inner, parallel, |_, lanes| {
+                    for k in 1..len {
+                        let (done, rest) = lanes.split_at_mut(k * inner);
+                        let (previous, row) = (&done[(k - 1) * inner..], &mut rest[..inner]);
+                        if row.len() >= simd::LANES
+                            && arith.is_some_and(|arith| simd::zip_assign(arith, row, previous))
+                        {
+                            continue;
+                        }
+                        for (x, &p) in row.iter_mut().zip(previous) {
+                            *x = self.apply(p, *x, undefined);
+                        }
+                    }
+                })
+            });
+            Ok(())
+        })?;
+        ArrayND::try_from_shape_vec(data, array.shape().to_vec())
+    }
+
+    /// Reduces the slices of `axis` starting at each of `indices`
+    ///
+    /// Slice `i` runs up to `indices[i + 1]`, or to the end of the axis for the
+    /// last one, and replaces the axis in the result. When `indices[i + 1]` is
+    /// not after `indices[i]` the result is just the element at `indices[i]`.
+    /// Panics if `axis` or one of the indices is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, BinaryUfunc};
+    /// let array = ArrayND::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    /// let sums = BinaryUfunc::ADD.reduceat(&array, &[0, 4, 1, 5], 0);
+    /// assert_eq!(sums.to_vec(), vec![6, 4, 10, 18]);
+    /// ```
+    pub fn reduceat<T: Element>(
+        &self,
+        array: &ArrayND<T>,
+        indices: &[usize],
+        axis: usize,
+    ) -> ArrayND<T>
+    where
+        F: BinaryOp<T>,
+    {
+        error::or_panic(self.try_reduceat(array, indices, axis))
+    }
+
+    /// Reduces the slices of `axis` starting at each of `indices`, failing if
+    /// `axis` or one of the indices is out of bounds, or the function is
+    /// undefined for some of the elements
+    pub fn try_reduceat<T: Element>(
+        &self,
+        array: &ArrayND<T>,
+        indices: &[usize],
+        axis: usize,
+    ) -> Result<ArrayND<T>>
+    where
+        F: BinaryOp<T>,
+    {
+        layout::check_axis(axis, array.ndim())?;
+        let (outer, len, inner) = split_shape(array.shape(), axis);
+        if let Some(index) = indices.iter().find(|&&index| index >= len) {
+            return Err(NumrsError::IndexOutOfBounds(format!(
+                "index {} is out of bounds for axis {} of length {}",
+                index, axis, len
+            )));
+        }
+        let mut shape = array.shape().to_vec();
+        shape[axis] = indices.len();
+
+        let data = array.contiguous_data();
+        let mut out = vec![T::zero(); outer * indices.len() * inner];
+        checked(|undefined| {
+            parallel::run(data.len(), |parallel| {
+                for_each_block(&mut out, indices.len() * inner, parallel, |i, rows| {
+                    let lanes = &data[i * len * inner..][..len * inner];
+                    for (j, row) in rows.chunks_mut(inner).enumerate() {
+                        let start = indices[j];
+                        let end = match indices.get(j + 1) {
+                            Some(&next) if next > start => next,
+                            Some(_) => start + 1,
+                            None => len,
+                        };
+                        row.copy_from_slice(&lanes[start * inner..][..inner]);
+                        for next in lanes[(start + 1) * inner..end * inner].chunks(inner) {
+                            self.combine(row, next, undefined);
+                        }
+                    }
+                })
+            });
+            Ok(())
+        })?;
+        ArrayND::try_from_shape_vec(out, shape)
+    }
+
+    /// Applies the function between every element of `a` and every element of
+    /// `b`, giving an Array with the axes of `a` followed by the axes of `b`
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::{ArrayND, BinaryUfunc};
+    /// let a = ArrayND::new(vec![1, 2, 3]);
+    /// let b = ArrayND::new(vec![10, 100]);
+    /// let products = BinaryUfunc::MUL.outer(&a, &b);
+    /// assert_eq!(products.shape(), &[3, 2]);
+    /// assert_eq!(products.to_vec(), vec![10, 100, 20, 200, 30, 300]);
+    /// ```
+    pub fn outer<T: Element>(&self, a: &ArrayND<T>, b: &ArrayND<T>) -> ArrayND<T>
+    where
+        F: BinaryOp<T>,
+    {
+        let (a_data, b_data) = (a.contiguous_data(), b.contiguous_data());
+        let mut data = vec![T::zero(); a_data.len() * b_data.len()];
+        let arith = self.arith.map(Arith::swapped);
+        parallel::run(data.len(), |parallel| {
+            for_each_block(&mut data, b_data.len(), parallel, |i, row| {
+                let x = a_data[i];
+                if row.len() >= simd::LANES
+                    && arith.is_some_and(|arith| simd::map(arith, &b_data, x, row))
+                {
+                    return;
+                }
+                for (z, &y) in row.iter_mut().zip(b_data.iter()) {
+                    *z = self.op.apply(x, y);
+                }
+            })
+        });
+        let shape = [a.shape(), b.shape()].concat();
+        ArrayND::from_shape_vec(data, shape)
+    }
+
+    /// The function of `x` and `y`, raising `undefined` where it has no value
+    fn apply<T: Element>(&self, x: T, y: T, undefined: &AtomicBool) -> T
+    where
+        F: BinaryOp<T>,
+    {
+        self.op.checked_apply(x, y).unwrap_or_else(|| {
+            undefined.store(true, Ordering::Relaxed);
+            T::zero()
+        })
+    }
+
+    /// Replaces every element of `lhs` with the function of it and the matching element of `rhs`
+    fn combine<T: Element>(&self, lhs: &mut [T], rhs: &[T], undefined: &AtomicBool)
+    where
+        F: BinaryOp<T>,
+    {
+        // Rows shorter than a vector are not worth a kernel call
+        if lhs.len() >= simd::LANES
+            && self
+                .arith
+                .is_some_and(|arith| simd::zip_assign(arith, lhs, rhs))
+        {
+            return;
+        }
+        for (x, &y) in lhs.iter_mut().zip(rhs) {
+            *x = self.apply(*x, y, undefined);
+        }
+    }
+}
+
+/// Runs `f` with a flag for `BinaryUfunc::apply`, failing if it was raised
+fn checked<R>(f: impl FnOnce(&AtomicBool) -> Result<R>) -> Result<R> {
+    let undefined = AtomicBool::new(false);
+    let result = f(&undefined)?;
+    if undefined.into_inner() {
+        return Err(NumrsError::Arithmetic(
+            "the function is undefined for some elements, like an integer division by zero"
+                .to_string(),
+        ));
+    }
+    Ok(result)
+}
+
+/// Number of elements before `axis`, along it and after it, so the row-major
+/// data is `outer` blocks of `len` rows of `inner` elements
+fn split_shape(shape: &[usize], axis: usize) -> (usize, usize, usize) {
+    let outer = shape[..axis].iter().product();
+    let inner = shape[axis + 1..].iter().product();
+    (outer, shape[axis], inner)
+}
+
+/// Calls `f` with the position and contents of every `len` long block of `data`
+#[cfg(target_family = "wasm")]
+fn for_each_block<T: Send>(
+    data: &mut [T],
+    len: usize,
+    _parallel: bool,
+    f: impl Fn(usize, &mut [T]) + Sync + Send,
+) {
+    if len > 0 {
+        data.chunks_mut(len)
+            .enumerate()
+            .for_each(|(i, block)| f(i, block));
+    }
+}
+
+/// Calls `f` with the position and contents of every `len` long block of
+/// `data`, on multiple threads if `parallel` is set
+#[cfg(target_family = "unix")]
+fn for_each_block<T: Send>(
+    data: &mut [T],
+    len: usize,
+    parallel: bool,
+    f: impl Fn(usize, &mut [T]) + Sync + Send,
+) {
+    if len == 0 {
+        return;
+    }
+    if parallel {
+        data.par_chunks_mut(len)
+            .enumerate()
+            .for_each(|(i, block)| f(i, block));
+    } else {
+        data.chunks_mut(len)
+            .enumerate()
+            .for_each(|(i, block)| f(i, block));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryOp, BinaryUfunc, UnaryUfunc};
+    use crate::{with_parallelism, ArrayND, DType, DynArray, Element, NumrsError, Parallelism};
+
+    #[test]
+    fn builtins_match_operators() {
+        let a: ArrayND = ArrayND::random(1_000);
+        let b: ArrayND = ArrayND::random(1_000);
+        assert_eq!(BinaryUfunc::ADD.call(&a, &b), &a + &b);
+        assert_eq!(BinaryUfunc::SUB.call(&a, &b), &a - &b);
+        assert_eq!(BinaryUfunc::MUL.call(&a, &b), &a * &b);
+        assert_eq!(BinaryUfunc::DIV.call(&a, &b), &a / &b);
+        assert_eq!(BinaryUfunc::MAXIMUM.call(&a, &b), a.clone().maximum(&b));
+        assert_eq!(BinaryUfunc::MINIMUM.call(&a, &b), a.clone().minimum(&b));
+
+        let ints = ArrayND::new(vec![3, -1, 2]);
+        let mut result = ints.clone();
+        BinaryUfunc::MINIMUM.call_mut(&mut result, &ArrayND::new(vec![0]));
+        assert_eq!(result.to_vec(), vec![0, -1, 0]);
+    }
+
+    #[test]
+    fn closures_broadcast() {
+        let column = ArrayND::from_shape_vec(vec![1., 2.], vec![2, 1]);
+        let row = ArrayND::new(vec![10., 20., 30.]);
+        let f = BinaryUfunc::new(|x: f64, y: f64| x * 100. + y);
+        let result = f.call(&column, &row);
+        assert_eq!(result.shape(), &[2, 3]);
+        assert_eq!(result.to_vec(), vec![110., 120., 130., 210., 220., 230.]);
+
+        let transposed = result.clone().transpose();
+        let negate = UnaryUfunc::new(|x: f64| -x);
+        assert_eq!(negate.call(&transposed), -1. * &transposed);
+        let mut in_place = transposed.clone();
+        negate.call_mut(&mut in_place);
+        assert_eq!(in_place, negate.call(&transposed));
+
+        assert!(matches!(
+            f.try_call(&row, &ArrayND::new(vec![1., 2.])),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn out_and_where() {
+        let a = ArrayND::from_shape_vec(vec![1, 2, 3, 4, 5, 6], vec![2, 3]);
+        let b = ArrayND::new(vec![10, 20, 30]);
+        let mut out = ArrayND::from_shape_vec(vec![0; 6], vec![2, 3]);
+        BinaryUfunc::ADD.call_into(&a, &b, &mut out);
+        assert_eq!(out.to_vec(), vec![11, 22, 33, 14, 25, 36]);
+        assert_eq!(out.max(), 36);
+
+        let mask = ArrayND::new(vec![true, false, true]);
+        BinaryUfunc::MUL.call_where(&a, &b, &mut out, &mask);
+        assert_eq!(out.to_vec(), vec![10, 22, 90, 40, 25, 180]);
+        assert_eq!(out.max(), 180);
+
+        let square = UnaryUfunc::new(|x: i32| x * x);
+        let mut strided = ArrayND::from_shape_vec(vec![0; 6], vec![3, 2]).transpose();
+        square.call_where(&a, &mut strided, &a.gt_scalar(4));
+        assert_eq!(strided.to_vec(), vec![0, 0, 0, 0, 25, 36]);
+        square.call_into(&b, &mut strided);
+        assert_eq!(strided.to_vec(), vec![100, 400, 900, 100, 400, 900]);
+    }
+
+    #[test]
+    fn out_errors_leave_out_unchanged() {
+        let a = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+        let mut out = ArrayND::new(vec![0., 0.]);
+        assert!(matches!(
+            BinaryUfunc::ADD.try_call_into(&a, &a, &mut out),
+            Err(NumrsError::ShapeMismatch(_))
+        ));
+        let mut out = ArrayND::from_shape_vec(vec![0.; 4], vec![2, 2]);
+        let mask = ArrayND::new(vec![true, false, true]);
+        assert!(BinaryUfunc::ADD
+            .try_call_where(&a, &a, &mut out, &mask)
+            .is_err());
+        assert_eq!(out.to_vec(), vec![0.; 4]);
+    }
+
+    #[test]
+    fn parallel_where_matches_sequential() {
+        let a: ArrayND = ArrayND::random(100_003);
+        let b: ArrayND = ArrayND::random(100_003);
+        let mask = a.gt_scalar(0.5);
+        let f = BinaryUfunc::new(|x: f64, y: f64| x.max(y) - 1.);
+        let run = |threshold| {
+            with_parallelism(&Parallelism::new().with_threshold(threshold), || {
+                let mut out = ArrayND::new(vec![0.; 100_003]);
+                f.call_where(&a, &b, &mut out, &mask);
+                let mut all = out.clone();
+                BinaryUfunc::ADD.call_into(&a, &b, &mut all);
+                (out, all)
+            })
+        };
+        let (sequential, parallel) = (run(usize::MAX), run(0));
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential.0.select(&!&mask).max(), 0.);
+        assert_eq!(sequential.1, &a + &b);
+    }
+
+    #[test]
+    fn reduce() {
+        let cube = ArrayND::from_shape_vec((0..24).collect(), vec![2, 3, 4]);
+        for axis in 0..3 {
+            for keepdims in [false, true] {
+                let sum = BinaryUfunc::ADD.reduce(&cube, axis, keepdims);
+                assert_eq!(sum, cube.sum_axis(axis, keepdims));
+                let prod = BinaryUfunc::MUL.reduce(&cube, axis, keepdims);
+                assert_eq!(prod, cube.prod_axis(axis, keepdims));
+                let max = BinaryUfunc::MAXIMUM.reduce(&cube, axis, keepdims);
+                assert_eq!(max, cube.max_axis(axis, keepdims));
+            }
+        }
+        let transposed = cube.clone().transpose();
+        assert_eq!(
+            BinaryUfunc::MINIMUM.reduce(&transposed, 1, false),
+            transposed.min_axis(1, false)
+        );
+
+        let difference = BinaryUfunc::new(|x: i32, y: i32| x - y);
+        let rows = ArrayND::from_shape_vec(vec![10, 1, 2, 20, 3, 4], vec![2, 3]);
+        assert_eq!(difference.reduce(&rows, 1, false).to_vec(), vec![7, 13]);
+    }
+
+    #[test]
+    fn reduce_empty_lanes() {
+        let empty: ArrayND = ArrayND::from_shape_vec(vec![], vec![3, 0]);
+        assert_eq!(
+            BinaryUfunc::ADD.reduce(&empty, 1, false).to_vec(),
+            vec![0.; 3]
+        );
+        assert_eq!(
+            BinaryUfunc::MUL.reduce(&empty, 1, true).to_vec(),
+            vec![1.; 3]
+        );
+        assert_eq!(BinaryUfunc::MAXIMUM.reduce(&empty, 0, false).shape(), &[0]);
+        assert!(matches!(
+            BinaryUfunc::MAXIMUM.try_reduce(&empty, 1, false),
+            Err(NumrsError::EmptyReduction(_))
+        ));
+        assert!(matches!(
+            BinaryUfunc::ADD.try_reduce(&empty, 2, false),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn accumulate() {
+        let matrix = ArrayND::from_shape_vec(vec![3., 1., 4., 1., 5., 9.], vec![2, 3]);
+        let cumsum = BinaryUfunc::ADD.accumulate(&matrix, 1);
+        assert_eq!(cumsum.to_vec(), vec![3., 4., 8., 1., 6., 15.]);
+        let running_max = BinaryUfunc::MAXIMUM.accumulate(&matrix.clone().transpose(), 1);
+        assert_eq!(running_max.to_vec(), vec![3., 3., 1., 5., 4., 9.]);
+
+        let wide: ArrayND = ArrayND::random(3 * 40).reshape(&[3, 40]);
+        let cumprod = BinaryUfunc::MUL.accumulate(&wide, 0).to_vec();
+        let expected: Vec<f64> = (0..120)
+            .map(|i| (0..=i / 40).map(|row| wide[[row, i % 40]]).product())
+            .collect();
+        assert_eq!(cumprod, expected);
+
+        let subtract = BinaryUfunc::new(|x: i64, y: i64| x - y);
+        let array = ArrayND::new(vec![10, 1, 2, 3]);
+        assert_eq!(subtract.accumulate(&array, 0).to_vec(), vec![10, 9, 7, 4]);
+        assert!(BinaryUfunc::ADD.try_accumulate(&array, 1).is_err());
+    }
+
+    #[test]
+    fn reduceat() {
+        let array = ArrayND::new((0..8).collect());
+        let indices = [0, 4, 1, 5, 2, 6, 3, 7];
+        let sums = BinaryUfunc::ADD.reduceat(&array, &indices, 0);
+        assert_eq!(sums.to_vec(), vec![6, 4, 10, 5, 14, 6, 18, 7]);
+
+        let matrix = ArrayND::from_shape_vec((0..12).collect(), vec![3, 4]);
+        let columns = BinaryUfunc::MAXIMUM.reduceat(&matrix, &[0, 2], 1);
+        assert_eq!(columns.shape(), &[3, 2]);
+        assert_eq!(columns.to_vec(), vec![1, 3, 5, 7, 9, 11]);
+        let rows = BinaryUfunc::ADD.reduceat(&matrix.clone().transpose(), &[1], 0);
+        assert_eq!(rows.to_vec(), vec![1 + 2 + 3, 5 + 6 + 7, 9 + 10 + 11]);
+        assert_eq!(BinaryUfunc::ADD.reduceat(&matrix, &[], 1).shape(), &[3, 0]);
+
+        assert!(matches!(
+            BinaryUfunc::ADD.try_reduceat(&matrix, &[0, 4], 1),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn outer() {
+        let a = ArrayND::from_shape_vec(vec![1., 2., 3., 4.], vec![2, 2]);
+        let b: ArrayND = ArrayND::random(20);
+        let products = BinaryUfunc::MUL.outer(&a, &b);
+        assert_eq!(products.shape(), &[2, 2, 20]);
+        for (i, &x) in a.to_vec().iter().enumerate() {
+            assert_eq!(products.to_vec()[i * 20..][..20], (&b * x).to_vec()[..]);
+        }
+        let differences = BinaryUfunc::SUB.outer(&a.clone().transpose(), &ArrayND::new(vec![1.]));
+        assert_eq!(differences.to_vec(), vec![0., 2., 1., 3.]);
+
+        let pairs = BinaryUfunc::new(|x: i32, y: i32| 10 * x + y);
+        let table = pairs.outer(&ArrayND::new(vec![1, 2]), &ArrayND::new(vec![3, 4, 5]));
+        assert_eq!(table.to_vec(), vec![13, 14, 15, 23, 24, 25]);
+        let empty = pairs.outer(&ArrayND::new(vec![1, 2]), &ArrayND::new(vec![]));
+        assert_eq!(empty.shape(), &[2, 0]);
+    }
+
+    #[test]
+    fn parallel_reductions_match_sequential() {
+        let array: ArrayND = ArrayND::random(300 * 500).reshape(&[300, 500]);
+        let run = |threshold| {
+            with_parallelism(&Parallelism::new().with_threshold(threshold), || {
+                (
+                    BinaryUfunc::MAXIMUM.reduce(&array, 1, false),
+                    BinaryUfunc::ADD.accumulate(&array, 1),
+                    BinaryUfunc::MUL.reduceat(&array, &[0, 100, 20], 1),
+                    BinaryUfunc::ADD.outer(&array.max_axis(1, false), &array.min_axis(0, false)),
+                )
+            })
+        };
+        assert_eq!(run(usize::MAX), run(0));
+    }
+
+    /// User-defined function for every element type
+    struct Midpoint;
+
+    impl<T: Element> BinaryOp<T> for Midpoint {
+        fn apply(&self, x: T, y: T) -> T {
+            T::from_f64((x.to_f64() + y.to_f64()) / 2.)
+        }
+    }
+
+    #[test]
+    fn dyn_dispatch() {
+        let ints = DynArray::from_f64_vec(vec![1., 4.], vec![2], DType::Int32);
+        let floats = DynArray::from_f64_vec(vec![2.], vec![1], DType::Float32);
+        let sum = BinaryUfunc::ADD.call_dyn(&ints, &floats);
+        assert_eq!(sum.dtype(), DType::Float64);
+        assert_eq!(sum.to_f64_vec(), vec![3., 6.]);
+
+        let midpoint = BinaryUfunc::new(Midpoint).call_dyn(&ints, &ints.clone().transpose());
+        assert_eq!(midpoint, ints);
+        let halves = BinaryUfunc::new(Midpoint).call_dyn(&ints, &floats);
+        assert_eq!(halves.to_f64_vec(), vec![1.5, 3.]);
+
+        let doubled = UnaryUfunc::new(Doubled).call_dyn(&ints);
+        assert_eq!(doubled.dtype(), DType::Int32);
+        assert_eq!(doubled.to_f64_vec(), vec![2., 8.]);
+
+        let bools = DynArray::from_f64_vec(vec![1., 0.], vec![2], DType::Bool);
+        assert!(matches!(
+            BinaryUfunc::ADD.try_call_dyn(&bools, &bools),
+            Err(NumrsError::UnsupportedDType(_))
+        ));
+        assert!(UnaryUfunc::new(Doubled).try_call_dyn(&bools).is_err());
+    }
+
+    #[test]
+    fn integer_division_errors() {
+        let ints = DynArray::from_f64_vec(vec![7., -8.], vec![2], DType::Int32);
+        let zeros = DynArray::from_f64_vec(vec![2., 0.], vec![2], DType::Int32);
+        assert!(matches!(
+            BinaryUfunc::DIV.try_call_dyn(&ints, &zeros),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        let floats = DynArray::from_f64_vec(vec![7., -8.], vec![2], DType::Float64);
+        let quotients = BinaryUfunc::DIV.call_dyn(&floats, &zeros);
+        assert_eq!(quotients.to_f64_vec(), vec![3.5, f64::NEG_INFINITY]);
+
+        let matrix = ArrayND::from_shape_vec(vec![8, 6, 0, i8::MIN, -1, 1], vec![2, 3]);
+        assert!(matches!(
+            BinaryUfunc::DIV.try_call(&matrix, &ArrayND::new(vec![1, 1, 0])),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        assert_eq!(
+            BinaryUfunc::DIV.reduce(&matrix, 0, false).to_vec(),
+            vec![0, -6, 0]
+        );
+        assert!(matches!(
+            BinaryUfunc::DIV.try_reduce(&matrix, 1, false),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        assert!(matches!(
+            BinaryUfunc::DIV.try_accumulate(&matrix, 1),
+            Err(NumrsError::Arithmetic(_))
+        ));
+        assert!(BinaryUfunc::DIV.try_reduceat(&matrix, &[1], 1).is_err());
+        assert_eq!(
+            BinaryUfunc::ADD.reduce(&matrix, 1, false).to_vec(),
+            vec![14, i8::MIN]
+        );
+    }
+
+    struct Doubled;
+
+    impl<T: Element + std::ops::Add<Output = T>> super::UnaryOp<T> for Doubled {
+        fn apply(&self, x: T) -> T {
+            x + x
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! Borrowed views into the buffer of an `ArrayND`
+//!
+//! A view is a start offset, shape and strides over a borrowed slice, so
+//! slicing, indexing and transposing a view never copies the data.
+
+use std::{
+    borrow::Cow,
+    ops::{Add, Index, IndexMut, Range},
+};
+
+use crate::{
+    broadcast, error, layout, parallel, simd,
+    slice::{self, SliceElem},
+    summation, ArrayND, Element, NdIndex, NumrsError, Result,
+};
+
+/// Read-only view into an Array
+///
+/// # Example
+/// ```
+/// use numrs::ArrayND;
+/// let array: ArrayND = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+/// let column = array.view().index_axis(1, 2);
+/// assert_eq!(column.to_vec(), vec![2., 5.]);
+/// ```
+#[derive(Clone)]
+pub struct ArrayView<'a, T = f64> {
+    data: &'a [T],
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<isize>,
+}
+
+/// Mutable view into an Array, writes go straight to the parent's buffer
+///
+/// # Example
+/// ```
+/// use numrs::ArrayND;
+/// let mut array: ArrayND = ArrayND::arange(0., 6., 1.).reshape(&[2, 3]);
+/// array.view_mut().index_axis(0, 1).fill(0.);
+/// assert_eq!(array.to_vec(), vec![0., 1., 2., 0., 0., 0.]);
+/// ```
+pub struct ArrayViewMut<'a, T = f64> {
+    data: &'a mut [T],
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<isize>,
+}
+
+impl<T: Element> ArrayND<T> {
+    /// Borrows the whole Array as a read-only view
+    pub fn view(&self) -> ArrayView<'_, T> {
+        ArrayView {
+            data: &self.data,
+            offset: 0,
+            shape: self.shape.clone(),
+            strides: self.strides.clone(),
+        }
+    }
+
+    /// Borrows the whole Array as a mutable view
+    ///
+    /// The cached min and max are discarded since the view may write to the data.
+    pub fn view_mut(&mut self) -> ArrayViewMut<'_, T> {
+        self.invalidate_stats();
+        ArrayViewMut {
+            data: &mut self.data,
+            offset: 0,
+            shape: self.shape.clone(),
+            strides: self.strides.clone(),
+        }
+    }
+}
+
+impl<'a, T: Element> ArrayView<'a, T> {
+    /// Length of each axis of the view
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Number of elements to step over in the parent's data to move along each axis
+    pub fn strides(&self) -> &[isize] {
+        &self.strides
+    }
+
+    /// Number of axes of the view
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Number of elements in the view
+    pub fn size(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Returns the element at the given multi-index
+    ///
+    /// Panics if the index has the wrong number of axes or is out of bounds.
+    pub fn item(&self, index: &[usize]) -> T {
+        error::or_panic(self.try_item(index))
+    }
+
+    /// Returns the element at the given multi-index, failing if it is out of bounds
+    pub fn try_item(&self, index: &[usize]) -> Result<T> {
+        Ok(self.data[layout::offset_of(self.offset, &self.shape, &self.strides, index)?])
+    }
+
+    /// Iterates over the elements in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        let data = self.data;
+        layout::Offsets::new(self.offset, &self.shape, &self.strides).map(move |i| &data[i])
+    }
+
+    /// Copies the elements into a Vec in row-major order
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().copied().collect()
+    }
+
+    /// Copies the view into a new, row-major Array
+    pub fn to_owned(&self) -> ArrayND<T> {
+        ArrayND::from_shape_vec(self.to_vec(), self.shape.clone())
+    }
+
+    /// Reverses the order of the axes
+    pub fn transpose(self) -> ArrayView<'a, T> {
+        let axes: Vec<usize> = (0..self.ndim()).rev().collect();
+        self.permute_axes(&axes)
+    }
+
+    /// Reorders the axes so that axis `i` of the result is axis `axes[i]` of the view
+    pub fn permute_axes(self, axes: &[usize]) -> ArrayView<'a, T> {
+        error::or_panic(self.try_permute_axes(axes))
+    }
+
+    /// Reorders the axes, failing if `axes` is not a permutation of `0..ndim`
+    pub fn try_permute_axes(self, axes: &[usize]) -> Result<ArrayView<'a, T>> {
+        let (shape, strides) = layout::permute(&self.shape, &self.strides, axes)?;
+        Ok(ArrayView {
+            shape,
+            strides,
+            ..self
+        })
+    }
+
+    /// Restricts `axis` to the positions in `range`
+    ///
+    /// # Example
+    /// ```
+    /// use numrs::ArrayND;
+    /// let array: ArrayND = ArrayND::arange(0., 10., 1.);
+    /// assert_eq!(array.view().slice_axis(0, 2..5).to_vec(), vec![2., 3., 4.]);
+    /// ```
+    pub fn slice_axis(self, axis: usize, range: Range<usize>) -> ArrayView<'a, T> {
+        error::or_panic(self.try_slice_axis(axis, range))
+    }
+
+    /// Restricts `axis` to the positions in `range`, failing if either is out of bounds
+    pub fn try_slice_axis(mut self, axis: usize, range: Range<usize>) -> Result<ArrayView<'a, T>> {
+        self.offset = layout::slice_axis(self.offset, &mut self.shape, &self.strides, axis, range)?;
+        Ok(self)
+    }
+
+    /// Selects position `index` along `axis`, removing that axis
+    pub fn index_axis(self, axis: usize, index: usize) -> ArrayView<'a, T> {
+        error::or_panic(self.try_index_axis(axis, index))
+    }
+
+    /// Selects position `index` along `axis`, failing if either is out of bounds
+    pub fn try_index_axis(mut self, axis: usize, index: usize) -> Result<ArrayView<'a, T>> {
+        self.offset =
+            layout::index_axis(self.offset, &mut self.shape, &mut self.strides, axis, index)?;
+        Ok(self)
+    }
+
+    /// Narrows the view to the positions selected by `info`, see [`s!`](crate::s)
+    ///
+    /// Panics if an index is out of bounds or `info` has more entries than the
+    /// view has axes.
+    pub fn slice(self, info: &[SliceElem]) -> ArrayView<'a, T> {
+        error::or_panic(self.try_slice(info))
+    }
+
+    /// Narrows the view to the positions selected by `info`, failing if an
+    /// index is out of bounds or `info` has too many entries
+    pub fn try_slice(mut self, info: &[SliceElem]) -> Result<ArrayView<'a, T>> {
+        let (offset, shape, strides) =
+            slice::slice_layout(self.offset, &self.shape, &self.strides, info)?;
+        self.offset = offset;
+        self.shape = shape;
+        self.strides = strides;
+        Ok(self)
+    }
+}
+
+impl<'a, T: Element + Add<Output = T>> ArrayView<'a, T> {
+    /// Sums the elements of the view with the same pairwise summation as
+    /// [`ArrayND::sum`], giving the same result as summing a copy of the view
+    pub fn sum(&self) -> T {
+        let data = if self.size() > 0 && layout::is_row_major(&self.shape, &self.strides) {
+            Cow::Borrowed(&self.data[self.offset..][..self.size()])
+        } else {
+            Cow::Owned(self.to_vec())
+        };
+        parallel::run(data.len(), |parallel| {
+            summation::blocked_sum(&data, parallel, simd::sum)
+        })
+    }
+}
+
+impl<'a, T: Element> ArrayViewMut<'a, T> {
+    /// Length of each axis of the view
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Number of elements to step over in the parent's data to move along each axis
+    pub fn strides(&self) -> &[isize] {
+        &self.strides
+    }
+
+    /// Number of axes of the view
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Number of elements in the view
+    pub fn size(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Reborrows as a read-only view
+    pub fn view(&self) -> ArrayView<'_, T> {
+        ArrayView {
+            data: &*self.data,
+            offset: self.offset,
+            shape: self.shape.clone(),
+            strides: self.strides.clone(),
+        }
+    }
+
+    /// Reborrows as a shorter lived mutable view
+    pub fn view_mut(&mut self) -> ArrayViewMut<'_, T> {
+        ArrayViewMut {
+            data: &mut *self.data,
+            offset: self.offset,
+            shape: self.shape.clone(),
+            strides: self.strides.clone(),
+        }
+    }
+
+    /// Returns the element at the given multi-index
+    ///
+    /// Panics if the index has the wrong number of axes or is out of bounds.
+    pub fn item(&self, index: &[usize]) -> T {
+        error::or_panic(self.try_item(index))
+    }
+
+    /// Returns the element at the given multi-index, failing if it is out of bounds
+    pub fn try_item(&self, index: &[usize]) -> Result<T> {
+        Ok(self.data[layout::offset_of(self.offset, &self.shape, &self.strides, index)?])
+    }
+
+    /// Overwrites the element at the given multi-index
+    ///
+    /// Panics if the index has the wrong number of axes or is out of bounds.
+    pub fn set(&mut self, index: &[usize], value: T) {
+        error::or_panic(self.try_set(index, value))
+    }
+
+    /// Overwrites the element at the given multi-index, failing if it is out of bounds
+    pub fn try_set(&mut self, index: &[usize], value: T) -> Result<()> {
+        self.data[layout::offset_of(self.offset, &self.shape, &self.strides, index)?] = value;
+        Ok(())
+    }
+
+    /// Sets every element of the view to `value`
+    pub fn fill(&mut self, value: T) {
+        self.map_inplace(|_| value);
+    }
+
+    /// Replaces every element of the view with `f` applied to it
+    pub fn map_inplace(&mut self, f: impl Fn(T) -> T) {
+        for i in layout::Offsets::new(self.offset, &self.shape, &self.strides) {
+            self.data[i] = f(self.data[i]);
+        }
+    }
+
+    /// Copies the elements of `other` into the view, broadcasting `other` if needed
+    ///
+    /// Panics if `other` cannot be broadcast to the shape of the view.
+    pub fn assign(&mut self, other: &ArrayView<T>) {
+        error::or_panic(self.try_assign(other))
+    }
+
+    /// Copies the elements of `other` into the view, failing without writing
+    /// anything if `other` cannot be broadcast to the shape of the view
+    pub fn try_assign(&mut self, other: &ArrayView<T>) -> Result<()> {
+        if broadcast::broadcast_shapes(&self.shape, &other.shape).as_deref() != Some(&self.shape) {
+            return Err(NumrsError::ShapeMismatch(format!(
+                "could not broadcast shape {:?} into shape {:?}",
+                other.shape, self.shape
+            )));
+        }
+        let strides = broadcast::broadcast_strides(&other.shape, &other.strides, &self.shape);
+        let targets = layout::Offsets::new(self.offset, &self.shape, &self.strides);
+        let sources = layout::Offsets::new(other.offset, &self.shape, &strides);
+        for (i, j) in targets.zip(sources) {
+            self.data[i] = other.data[j];
+        }
+        Ok(())
+    }
+
+    /// Reverses the order of the axes
+    pub fn transpose(self) -> ArrayViewMut<'a, T> {
+        let axes: Vec<usize> = (0..self.ndim()).rev().collect();
+        self.permute_axes(&axes)
+    }
+
+    /// Reorders the axes so that axis `i` of the result is axis `axes[i]` of the view
+    pub fn permute_axes(self, axes: &[usize]) -> ArrayViewMut<'a, T> {
+        error::or_panic(self.try_permute_axes(axes))
+    }
+
+    /// Reorders the axes, failing if `axes` is not a permutation of `0..ndim`
+    pub fn try_permute_axes(mut self, axes: &[usize]) -> Result<ArrayViewMut<'a, T>> {
+        let (shape, strides) = layout::permute(&self.shape, &self.strides, axes)?;
+        self.shape = shape;
+        self.strides = strides;
+        Ok(self)
+    }
+
+    /// Restricts `axis` to the positions in `range`
+    pub fn slice_axis(self, axis: usize, range: Range<usize>) -> ArrayViewMut<'a, T> {
+        error::or_panic(self.try_slice_axis(axis, range))
+    }
+
+    /// Restricts `axis` to the positions in `range`, failing if either is out of bounds
+    pub fn try_slice_axis(
+        mut self,
+        axis: usize,
+        range: Range<usize>,
+    ) -> Result<ArrayViewMut<'a, T>> {
+        self.offset = layout::slice_axis(self.offset, &mut self.shape, &self.strides, axis, range)?;
+        Ok(self)
+    }
+
+    /// Selects position `index` along `axis`, removing that axis
+    pub fn index_axis(self, axis: usize, index: usize) -> ArrayViewMut<'a, T> {
+        error::or_panic(self.try_index_axis(axis, index))
+    }
+
+    /// Selects position `index` along `axis`, failing if either is out of bounds
+    pub fn try_index_axis(mut self, axis: usize, index: usize) -> Result<ArrayViewMut<'a, T>> {
+        self.offset =
+            layout::index_axis(self.offset, &mut self.shape, &mut self.strides, axis, index)?;
+        Ok(self)
+    }
+
+    /// Narrows the view to the positions selected by `info`, see [`s!`](crate::s)
+    ///
+    /// Panics if an index is out of bounds or `info` has more entries than the
+    /// view has axes.
+    pub fn slice(self, info: &[SliceElem]) -> ArrayViewMut<'a, T> {
+        error::or_panic(self.try_slice(info))
+    }
+
+    /// Narrows the view to the positions selected by `info`, failing if an
+    /// index is out of bounds or `info` has too many entries
+    pub fn try_slice(mut self, info: &[SliceElem]) -> Result<ArrayViewMut<'a, T>> {
+        let (offset, shape, strides) =
+            slice::slice_layout(self.offset, &self.shape, &self.strides, info)?;
+        self.offset = offset;
+        self.shape = shape;
+        self.strides = strides;
+        Ok(self)
+    }
+}
+
+impl<'a, T: Element> ArrayView<'a, T> {
+    /// Reference to the element at `index`, or `None` if it is out of bounds
+    pub fn get<I: NdIndex>(&self, index: I) -> Option<&'a T> {
+        let offset = index.offset(self.offset, &self.shape, &self.strides).ok()?;
+        Some(&self.data[offset])
+    }
+}
+
+impl<T: Element, I: NdIndex> Index<I> for ArrayView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: I) -> &T {
+        &self.data[error::or_panic(index.offset(self.offset, &self.shape, &self.strides))]
+    }
+}
+
+impl<T: Element> ArrayViewMut<'_, T> {
+    /// Reference to the element at `index`, or `None` if it is out of bounds
+    pub fn get<I: NdIndex>(&self, index: I) -> Option<&T> {
+        let offset = index.offset(self.offset, &self.shape, &self.strides).ok()?;
+        Some(&self.data[offset])
+    }
+
+    /// Mutable reference to the element at `index`, or `None` if it is out of bounds
+    pub fn get_mut<I: NdIndex>(&mut self, index: I) -> Option<&mut T> {
+        let offset = index.offset(self.offset, &self.shape, &self.strides).ok()?;
+        Some(&mut self.data[offset])
+    }
+}
+
+impl<T: Element, I: NdIndex> Index<I> for ArrayViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: I) -> &T {
+        &self.data[error::or_panic(index.offset(self.offset, &self.shape, &self.strides))]
+    }
+}
+
+impl<T: Element, I: NdIndex> IndexMut<I> for ArrayViewMut<'_, T> {
+    fn index_mut(&mut self, index: I) -> &mut T {
+        let offset = error::or_panic(index.offset(self.offset, &self.shape, &self.strides));
+        &mut self.data[offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayND, NumrsError};
+
+    fn get_matrix() -> ArrayND {
+        ArrayND::arange(0., 12., 1.).reshape(&[3, 4])
+    }
+
+    #[test]
+    fn sum_matches_array_sum() {
+        let array: ArrayND = ArrayND::random(100_000).reshape(&[400, 250]);
+        assert_eq!(array.view().sum().to_bits(), array.sum().to_bits());
+        let rows = array.view().slice_axis(0, 10..300);
+        assert_eq!(rows.sum().to_bits(), rows.to_owned().sum().to_bits());
+        let columns = array.view().slice_axis(1, 3..200);
+        assert_eq!(columns.sum().to_bits(), columns.to_owned().sum().to_bits());
+    }
+
+    #[test]
+    fn view() {
+        let array = get_matrix();
+        let view = array.view();
+
+        assert_eq!(view.shape(), &[3, 4]);
+        assert_eq!(view.item(&[2, 1]), 9.);
+        assert_eq!(view.sum(), 66.);
+        assert_eq!(view.to_owned(), array);
+    }
+
+    #[test]
+    fn view_sub_block() {
+        let array = get_matrix();
+        let block = array.view().slice_axis(0, 1..3).slice_axis(1, 1..3);
+
+        assert_eq!(block.shape(), &[2, 2]);
+        assert_eq!(block.to_vec(), vec![5., 6., 9., 10.]);
+        assert_eq!(block.clone().transpose().to_vec(), vec![5., 9., 6., 10.]);
+        assert_eq!(block.index_axis(0, 1).to_vec(), vec![9., 10.]);
+        assert_eq!(array.view().slice_axis(1, 2..2).size(), 0);
+    }
+
+    #[test]
+    fn view_of_transposed() {
+        let array = get_matrix().transpose();
+        let row = array.view().index_axis(0, 3);
+
+        assert_eq!(row.to_vec(), vec![3., 7., 11.]);
+        assert_eq!(row.strides(), &[4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn view_slice_out_of_bounds() {
+        let array = get_matrix();
+        array.view().slice_axis(0, 1..4);
+    }
+
+    #[test]
+    fn view_try_errors() {
+        let mut array = get_matrix();
+
+        assert!(matches!(
+            array.view().try_slice_axis(2, 0..1),
+            Err(NumrsError::IndexOutOfBounds(_))
+        ));
+        assert!(array.view().try_index_axis(0, 3).is_err());
+        assert!(array.view_mut().try_set(&[3, 0], 1.).is_err());
+
+        let column = ArrayND::new(vec![1., 2.]);
+        let result = array.view_mut().try_assign(&column.view());
+        assert!(matches!(result, Err(NumrsError::ShapeMismatch(_))));
+        assert_eq!(array, get_matrix());
+    }
+
+    #[test]
+    fn view_mut_writes_through() {
+        let mut array = get_matrix();
+        {
+            let mut block = array.view_mut().slice_axis(0, 0..2).slice_axis(1, 2..4);
+            block.map_inplace(|x| -x);
+            block.set(&[0, 0], 100.);
+        }
+
+        assert_eq!(array.item(&[0, 2]), 100.);
+        assert_eq!(array.item(&[1, 3]), -7.);
+        assert_eq!(array.item(&[2, 3]), 11.);
+        assert_eq!(array.min(), -7.);
+        assert_eq!(array.max(), 100.);
+    }
+
+    #[test]
+    fn view_mut_assign() {
+        let mut array = get_matrix();
+        let row = ArrayND::new(vec![-1., -2., -3., -4.]);
+        array.view_mut().slice_axis(0, 1..3).assign(&row.view());
+
+        assert_eq!(array.view().index_axis(0, 0).to_vec(), vec![0., 1., 2., 3.]);
+        assert_eq!(
+            array.view().index_axis(0, 2).to_vec(),
+            vec![-1., -2., -3., -4.]
+        );
+        assert_eq!(array.min(), -4.);
+
+        array.view_mut().transpose().index_axis(0, 0).fill(7.);
+        assert_eq!(array.view().index_axis(1, 0).to_vec(), vec![7., 7., 7.]);
+    }
+}
@@ -0,0 +1,264 @@
+//! JavaScript bindings
+//!
+//! wasm-bindgen cannot export generic types, so JS gets a single `ArrayND`
+//! class backed by a `DynArray`. The element type is picked with an optional
+//! `DType` argument and defaults to `Float64`; values cross the boundary as
+//! `f64`.
+//!
+//! Every export that can fail goes through the `try_` methods and throws a JS
+//! `Error` with the `NumrsError` message, since a panic would abort the whole
+//! wasm instance.
+
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+
+use crate::{Casting, DType, DynArray};
+
+/// `DynArray` as seen from JavaScript
+#[wasm_bindgen(js_name = ArrayND)]
+pub struct WasmArray {
+    inner: DynArray,
+}
+
+impl From<DynArray> for WasmArray {
+    fn from(inner: DynArray) -> WasmArray {
+        WasmArray { inner }
+    }
+}
+
+#[wasm_bindgen(js_class = ArrayND)]
+impl WasmArray {
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: Vec<f64>, dtype: Option<DType>) -> WasmArray {
+        let shape = vec![data.len()];
+        DynArray::from_f64_vec(data, shape, dtype.unwrap_or(DType::Float64)).into()
+    }
+
+    pub fn from_shape_vec(
+        data: Vec<f64>,
+        shape: Vec<usize>,
+        dtype: Option<DType>,
+    ) -> Result<WasmArray, JsError> {
+        let array = DynArray::try_from_f64_vec(data, shape, dtype.unwrap_or(DType::Float64))?;
+        Ok(array.into())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dtype(&self) -> DType {
+        self.inner.dtype()
+    }
+
+    /// Copies the Array into one of type `dtype`, throwing if `casting` does not
+    /// allow it. Casting defaults to `Unsafe`.
+    pub fn astype(&self, dtype: DType, casting: Option<Casting>) -> Result<WasmArray, JsError> {
+        let array = self
+            .inner
+            .astype(dtype, casting.unwrap_or(Casting::Unsafe))?;
+        Ok(array.into())
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.inner.ndim()
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.inner.shape().to_vec()
+    }
+
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.inner.to_f64_vec()
+    }
+
+    /// Element at position `index` in row-major order, negative indices count
+    /// from the end. Throws if the index is out of bounds.
+    pub fn get(&self, index: isize) -> Result<f64, JsError> {
+        self.inner.get(index).ok_or_else(|| {
+            JsError::new(&format!(
+                "index {} is out of bounds for array of shape {:?}",
+                index,
+                self.inner.shape()
+            ))
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> Result<f64, JsError> {
+        Ok(self.inner.try_min()?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> Result<f64, JsError> {
+        Ok(self.inner.try_max()?)
+    }
+
+    pub fn nanmin(&self) -> Result<f64, JsError> {
+        Ok(self.inner.try_nanmin()?)
+    }
+
+    pub fn nanmax(&self) -> Result<f64, JsError> {
+        Ok(self.inner.try_nanmax()?)
+    }
+
+    pub fn flatten(&self) -> WasmArray {
+        self.inner.flatten().into()
+    }
+
+    pub fn ravel(self) -> WasmArray {
+        self.inner.ravel().into()
+    }
+
+    pub fn transpose(self) -> WasmArray {
+        self.inner.transpose().into()
+    }
+
+    pub fn reshape(self, shape: Vec<isize>) -> Result<WasmArray, JsError> {
+        Ok(self.inner.try_reshape(&shape)?.into())
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.inner.sum()
+    }
+
+    pub fn nansum(&self) -> f64 {
+        self.inner.nansum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.inner.mean()
+    }
+
+    pub fn nanmean(&self) -> f64 {
+        self.inner.nanmean()
+    }
+
+    pub fn random(size: usize, dtype: Option<DType>) -> WasmArray {
+        DynArray::random(size, dtype.unwrap_or(DType::Float64)).into()
+    }
+
+    pub fn arange(start: f64, stop: f64, step: f64, dtype: Option<DType>) -> WasmArray {
+        DynArray::arange(start, stop, step, dtype.unwrap_or(DType::Float64)).into()
+    }
+
+    pub fn add(self, num: f64) -> Result<WasmArray, JsError> {
+        Ok(self.inner.try_add_scalar(num)?.into())
+    }
+
+    pub fn sub(self, num: f64) -> Result<WasmArray, JsError> {
+        Ok(self.inner.try_sub_scalar(num)?.into())
+    }
+
+    pub fn mul(self, num: f64) -> Result<WasmArray, JsError> {
+        Ok(self.inner.try_mul_scalar(num)?.into())
+    }
+
+    pub fn div(self, num: f64) -> Result<WasmArray, JsError> {
+        Ok(self.inner.try_div_scalar(num)?.into())
+    }
+
+    pub fn add_array(&self, other: &WasmArray) -> Result<WasmArray, JsError> {
+        Ok(self.inner.clone().try_add(&other.inner)?.into())
+    }
+
+    pub fn sub_array(&self, other: &WasmArray) -> Result<WasmArray, JsError> {
+        Ok(self.inner.clone().try_sub(&other.inner)?.into())
+    }
+
+    pub fn mul_array(&self, other: &WasmArray) -> Result<WasmArray, JsError> {
+        Ok(self.inner.clone().try_mul(&other.inner)?.into())
+    }
+
+    pub fn div_array(&self, other: &WasmArray) -> Result<WasmArray, JsError> {
+        Ok(self.inner.clone().try_div(&other.inner)?.into())
+    }
+
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// Exports math functions of one argument, consuming and in place
+macro_rules! export_unary {
+    ($($method:ident, $method_mut:ident;)*) => {
+        #[wasm_bindgen(js_class = ArrayND)]
+        impl WasmArray {
+            $(
+                pub fn $method(self) -> WasmArray {
+                    self.inner.$method().into()
+                }
+
+                pub fn $method_mut(&mut self) {
+                    self.inner.$method_mut()
+                }
+            )*
+        }
+    };
+}
+
+/// Exports math functions of two arguments, with an Array or a scalar second argument
+macro_rules! export_binary {
+    ($($method:ident, $try_method:ident, $try_method_mut:ident, $method_mut:ident, $scalar_method:ident, $scalar_method_mut:ident;)*) => {
+        #[wasm_bindgen(js_class = ArrayND)]
+        impl WasmArray {
+            $(
+                pub fn $method(&self, other: &WasmArray) -> Result<WasmArray, JsError> {
+                    Ok(self.inner.clone().$try_method(&other.inner)?.into())
+                }
+
+                pub fn $method_mut(&mut self, other: &WasmArray) -> Result<(), JsError> {
+                    Ok(self.inner.$try_method_mut(&other.inner)?)
+                }
+
+                pub fn $scalar_method(self, num: f64) -> WasmArray {
+                    self.inner.$scalar_method(num).into()
+                }
+
+                pub fn $scalar_method_mut(&mut self, num: f64) {
+                    self.inner.$scalar_method_mut(num)
+                }
+            )*
+        }
+    };
+}
+
+export_unary!(
+    exp, exp_mut;
+    exp2, exp2_mut;
+    ln, ln_mut;
+    log2, log2_mut;
+    log10, log10_mut;
+    log1p, log1p_mut;
+    expm1, expm1_mut;
+    sqrt, sqrt_mut;
+    cbrt, cbrt_mut;
+    abs, abs_mut;
+    sign, sign_mut;
+    sin, sin_mut;
+    cos, cos_mut;
+    tan, tan_mut;
+    asin, asin_mut;
+    acos, acos_mut;
+    atan, atan_mut;
+    sinh, sinh_mut;
+    cosh, cosh_mut;
+    tanh, tanh_mut;
+    asinh, asinh_mut;
+    acosh, acosh_mut;
+    atanh, atanh_mut;
+    floor, floor_mut;
+    ceil, ceil_mut;
+    round, round_mut;
+    trunc, trunc_mut;
+);
+
+export_binary!(
+    pow, try_pow, try_pow_mut, pow_mut, pow_scalar, pow_scalar_mut;
+    hypot, try_hypot, try_hypot_mut, hypot_mut, hypot_scalar, hypot_scalar_mut;
+    atan2, try_atan2, try_atan2_mut, atan2_mut, atan2_scalar, atan2_scalar_mut;
+    maximum, try_maximum, try_maximum_mut, maximum_mut, maximum_scalar, maximum_scalar_mut;
+    minimum, try_minimum, try_minimum_mut, minimum_mut, minimum_scalar, minimum_scalar_mut;
+);
+
+#[wasm_bindgen(js_name = asarray)]
+pub fn wasm_asarray(data: Vec<f64>, dtype: Option<DType>) -> WasmArray {
+    WasmArray::new(data, dtype)
+}